@@ -0,0 +1,113 @@
+#![no_std]
+
+elrond_wasm::imports!();
+
+extern crate aggregator;
+
+use crate::aggregator::aggregator_interface::Round;
+
+/// Proxy round ids hold the phase id in the bits above this offset and the
+/// round id of the phase's aggregator in the bits below it.
+pub const PHASE_OFFSET: u64 = 48;
+
+pub fn add_phase(phase_id: u16, aggregator_round_id: u64) -> u64 {
+    ((phase_id as u64) << PHASE_OFFSET) | aggregator_round_id
+}
+
+pub fn parse_round_id(round_id: u64) -> (u16, u64) {
+    (
+        (round_id >> PHASE_OFFSET) as u16,
+        round_id & ((1u64 << PHASE_OFFSET) - 1),
+    )
+}
+
+#[elrond_wasm_derive::contract]
+pub trait AggregatorProxy {
+    #[init]
+    fn init(&self, aggregator: Address) {
+        self.set_aggregator_internal(aggregator);
+    }
+
+    /// Points the proxy to a new aggregator deployment, starting a new phase.
+    /// Rounds of the previous phases remain accessible through `getRoundData`.
+    #[endpoint(setAggregator)]
+    fn set_aggregator(&self, aggregator: Address) -> SCResult<()> {
+        only_owner!(self, "Only owner may call this function!");
+        self.set_aggregator_internal(aggregator);
+        Ok(())
+    }
+
+    fn set_aggregator_internal(&self, aggregator: Address) {
+        let phase_id = self.phase_id().get() + 1;
+        self.phase_id().set(&phase_id);
+        self.phase_aggregators().insert(phase_id, aggregator.clone());
+        self.aggregator_updated_event(phase_id, &aggregator);
+    }
+
+    /// The aggregators are queried synchronously, so they must reside in the same shard
+    /// as the proxy.
+    #[endpoint(latestRoundData)]
+    fn latest_round_data(&self) -> SCResult<OptionalResult<Round<Self::BigUint>>> {
+        let phase_id = self.phase_id().get();
+        let round = self
+            .aggregator_proxy(self.get_phase_aggregator(phase_id)?)
+            .latest_round_data()
+            .execute_on_dest_context();
+        Ok(self.add_phase_to_round(phase_id, round))
+    }
+
+    #[endpoint(getRoundData)]
+    fn get_round_data(&self, round_id: u64) -> SCResult<OptionalResult<Round<Self::BigUint>>> {
+        let (phase_id, aggregator_round_id) = parse_round_id(round_id);
+        let round = self
+            .aggregator_proxy(self.get_phase_aggregator(phase_id)?)
+            .get_round_data(aggregator_round_id)
+            .execute_on_dest_context();
+        Ok(self.add_phase_to_round(phase_id, round))
+    }
+
+    #[view(aggregator)]
+    fn aggregator(&self) -> SCResult<Address> {
+        self.get_phase_aggregator(self.phase_id().get())
+    }
+
+    fn get_phase_aggregator(&self, phase_id: u16) -> SCResult<Address> {
+        if let Some(aggregator) = self.phase_aggregators().get(&phase_id) {
+            return Ok(aggregator);
+        }
+        sc_error!("No aggregator for given phase")
+    }
+
+    fn add_phase_to_round(
+        &self,
+        phase_id: u16,
+        round: OptionalResult<Round<Self::BigUint>>,
+    ) -> OptionalResult<Round<Self::BigUint>> {
+        round
+            .into_option()
+            .map(|mut round| {
+                round.round_id = add_phase(phase_id, round.round_id);
+                round.answered_in_round = add_phase(phase_id, round.answered_in_round);
+                round
+            })
+            .into()
+    }
+
+    #[view(phaseId)]
+    #[storage_mapper("phase_id")]
+    fn phase_id(&self) -> SingleValueMapper<Self::Storage, u16>;
+
+    #[storage_mapper("phase_aggregators")]
+    fn phase_aggregators(&self) -> MapMapper<Self::Storage, u16, Address>;
+
+    #[view(phaseAggregator)]
+    fn phase_aggregator(&self, phase_id: u16) -> OptionalResult<Address> {
+        self.phase_aggregators().get(&phase_id).into()
+    }
+
+    #[proxy]
+    fn aggregator_proxy(&self, to: Address) -> aggregator::Proxy<Self::SendApi>;
+
+    #[event("aggregator_updated")]
+    fn aggregator_updated_event(&self, #[indexed] phase_id: u16, aggregator: &Address);
+}
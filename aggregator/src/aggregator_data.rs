@@ -2,19 +2,50 @@ elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
 
 pub use crate::aggregator_interface::Submission;
+use crate::aggregator_interface::OracleConfig;
 
 #[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
 pub struct RoundDetails<BigUint: BigUintApi> {
-    pub submissions: Vec<Submission<BigUint>>,
     pub max_submissions: u64,
     pub min_submissions: u64,
     pub timeout: u64,
     pub payment_amount: BigUint,
+    pub payment_token: TokenIdentifier,
+    pub bonus_submissions: u64,
+    pub bonus_amount: BigUint,
+    pub values_count: usize,
+    /// The negative value bound when the round started; signed values are aggregated
+    /// as `value_offset + value`.
+    pub value_offset: BigUint,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct RoundSubmission<BigUint: BigUintApi> {
+    pub oracle: Address,
+    pub submission: Submission<BigUint>,
+    pub timestamp: u64,
+}
+
+/// The payments earned by an oracle during an epoch, not yet added to its withdrawable
+/// balance
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct PendingEarnings<BigUint: BigUintApi> {
+    pub epoch: u64,
+    pub token_id: TokenIdentifier,
+    pub amount: BigUint,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct EscrowedPayment<BigUint: BigUintApi> {
+    pub oracle: Address,
+    pub token_id: TokenIdentifier,
+    pub amount: BigUint,
+    /// The part of the payment not covered by the available funds
+    pub owed: BigUint,
 }
 
 #[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
 pub struct OracleStatus<BigUint: BigUintApi> {
-    pub withdrawable: BigUint,
     pub starting_round: u64,
     pub ending_round: u64,
     pub last_reported_round: u64,
@@ -22,6 +53,47 @@ pub struct OracleStatus<BigUint: BigUintApi> {
     pub latest_submission: Option<Submission<BigUint>>,
     pub admin: Address,
     pub pending_admin: Option<Address>,
+    pub payout_address: Option<Address>,
+    /// Overrides the restart delay of the feed for this oracle
+    pub restart_delay: Option<u64>,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi, Default)]
+pub struct OracleStats {
+    pub rounds_eligible: u64,
+    pub rounds_submitted: u64,
+    pub rounds_missed: u64,
+    pub total_submission_latency: u64,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct FeedConfig {
+    pub decimals: u8,
+    pub description: BoxedBytes,
+    pub values_count: usize,
+    pub value_decimals: Vec<u8>,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct SubmissionBounds<BigUint: BigUintApi> {
+    pub min: BigUint,
+    pub max: BigUint,
+}
+
+/// A contract notified of new answers. `balance` is the EGLD prepaid for its
+/// notifications, each of which costs `gas_limit` times the notification gas price.
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct Subscriber<BigUint: BigUintApi> {
+    pub callback: BoxedBytes,
+    pub gas_limit: u64,
+    pub balance: BigUint,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct OracleStake<BigUint: BigUintApi> {
+    pub bonded: BigUint,
+    pub unbonding: BigUint,
+    pub unbonding_ends_at: u64,
 }
 
 #[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
@@ -31,6 +103,58 @@ pub struct Requester {
     pub last_started_round: u64,
 }
 
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct EmergencyWithdrawal<BigUint: BigUintApi> {
+    pub token_id: TokenIdentifier,
+    pub amount: BigUint,
+    pub recipient: Address,
+    pub execute_after: u64,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct FutureRoundsProposal<BigUint: BigUintApi> {
+    pub payment_amount: BigUint,
+    pub min_submissions: u64,
+    pub max_submissions: u64,
+    pub restart_delay: u64,
+    pub timeout: u64,
+    pub execute_after: u64,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct OracleChangeProposal {
+    pub removed: Vec<Address>,
+    pub added: Vec<Address>,
+    pub added_admins: Vec<Address>,
+    pub added_weights: Option<Vec<u64>>,
+    pub min_submissions: u64,
+    pub max_submissions: u64,
+    pub restart_delay: u64,
+    pub execute_after: u64,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct OracleConfigsProposal<BigUint: BigUintApi> {
+    pub configs: Vec<OracleConfig<BigUint>>,
+    pub min_submissions: u64,
+    pub max_submissions: u64,
+    pub restart_delay: u64,
+    pub execute_after: u64,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct PaymentTokenProposal<BigUint: BigUintApi> {
+    pub token_id: TokenIdentifier,
+    pub payment_amount: BigUint,
+    pub execute_after: u64,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct TokenMigrationProposal {
+    pub new_token: TokenIdentifier,
+    pub execute_after: u64,
+}
+
 #[derive(TopEncode, TopDecode, PartialEq, Clone, Copy)]
 pub struct Funds<BigUint: BigUintApi> {
     pub available: BigUint,
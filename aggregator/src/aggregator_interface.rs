@@ -3,9 +3,59 @@
 elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
 
+/// `negative` flags the negative values of signed feeds, whose `values` hold the
+/// magnitudes. It is empty when none of the values is negative.
 #[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi, PartialEq, Debug, Clone)]
 pub struct Submission<BigUint: BigUintApi> {
     pub values: Vec<BigUint>,
+    pub negative: Vec<bool>,
+}
+
+impl<BigUint: BigUintApi> Submission<BigUint> {
+    pub fn from_values(values: Vec<BigUint>) -> Self {
+        Submission {
+            values,
+            negative: Vec::new(),
+        }
+    }
+
+    pub fn is_negative(&self, index: usize) -> bool {
+        self.negative.get(index).cloned().unwrap_or(false)
+    }
+
+    /// Zero is never negative, and the flags are dropped when no value is negative.
+    pub fn normalize_signs(&mut self) {
+        for (index, value) in self.values.iter().enumerate() {
+            if value == &BigUint::zero() {
+                if let Some(negative) = self.negative.get_mut(index) {
+                    *negative = false;
+                }
+            }
+        }
+        if !self.negative.iter().any(|negative| *negative) {
+            self.negative.clear();
+        }
+    }
+}
+
+/// The statistic used to compute a round's answer from the oracle submissions
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi, PartialEq, Debug, Clone, Copy)]
+pub enum AggregationStrategy {
+    Median,
+    Mean,
+    Mode,
+    TrimmedMean,
+}
+
+/// What happens to a round which times out without an answer
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi, PartialEq, Debug, Clone, Copy)]
+pub enum TimeoutPolicy {
+    /// The round carries the answer of the previous round
+    CarryForward,
+    /// The round is left without an answer and flagged as stale
+    MarkStale,
+    /// Like `MarkStale`, and no new round can start until governance resumes the feed
+    Halt,
 }
 
 #[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
@@ -17,4 +67,59 @@ pub struct Round<BigUint: BigUintApi> {
     pub started_at: u64,
     pub updated_at: u64,
     pub answered_in_round: u64,
+    /// Spread (max - min) of the submissions for each value of the answer
+    pub dispersion: Vec<BigUint>,
+    /// Age of the oldest, median and newest submission when the answer was computed
+    pub min_observation_age: u64,
+    pub median_observation_age: u64,
+    pub max_observation_age: u64,
+    /// False while the round has no answer of its own, e.g. when it timed out and carries
+    /// the answer of a previous round
+    pub fresh: bool,
+}
+
+/// The parameters of the feed in force for new rounds, as returned by `getConfig`
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct AggregatorConfig<BigUint: BigUintApi> {
+    pub payment_amount: BigUint,
+    pub min_submissions: u64,
+    pub max_submissions: u64,
+    pub restart_delay: u64,
+    pub timeout: u64,
+    pub min_submission_value: BigUint,
+    pub max_submission_value: BigUint,
+    pub decimals: u8,
+    pub description: BoxedBytes,
+    pub values_count: usize,
+    pub token_id: TokenIdentifier,
+}
+
+/// What consumer UIs need to render a feed, as returned by `getFeedInfo`
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct FeedInfo<BigUint: BigUintApi> {
+    pub description: BoxedBytes,
+    pub decimals: u8,
+    pub values_count: usize,
+    pub latest_round_id: u64,
+    pub latest_answer: Option<Submission<BigUint>>,
+    pub latest_timestamp: u64,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct ReportObservation<BigUint: BigUintApi> {
+    pub oracle: Address,
+    pub values: Vec<BigUint>,
+    /// Flags the negative values, as in `Submission`
+    pub negative: Vec<bool>,
+    pub signature: BoxedBytes,
+}
+
+/// The complete configuration of an oracle, as set by `configureOracles`
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct OracleConfig<BigUint: BigUintApi> {
+    pub address: Address,
+    pub admin: Address,
+    pub weight: u64,
+    /// Paid to this oracle instead of the round's payment amount
+    pub payment_override: Option<BigUint>,
 }
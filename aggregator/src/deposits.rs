@@ -0,0 +1,52 @@
+elrond_wasm::imports!();
+
+/// Shares minted for a deposit of `amount` into a pool worth `pool`, which is split into
+/// `total_shares`, followed by the total shares after the deposit. The first deposit into
+/// an empty pool mints one share per unit. Funds of a pool without shares belong to no
+/// depositor: they are backed by one share per unit which nobody holds, so that the
+/// depositor can only claim its deposit.
+pub fn shares_for_deposit<BigUint: BigUintApi>(
+    amount: &BigUint,
+    pool: &BigUint,
+    total_shares: &BigUint,
+) -> (BigUint, BigUint) {
+    if pool == &BigUint::zero() {
+        return (amount.clone(), total_shares.clone() + amount.clone());
+    }
+    if total_shares == &BigUint::zero() {
+        return (amount.clone(), pool.clone() + amount.clone());
+    }
+    let shares = amount.clone() * total_shares.clone() / pool.clone();
+    (shares.clone(), total_shares.clone() + shares)
+}
+
+/// The part of the pool owned by `shares`, rounded down.
+pub fn claim_for_shares<BigUint: BigUintApi>(
+    shares: &BigUint,
+    pool: &BigUint,
+    total_shares: &BigUint,
+) -> BigUint {
+    if total_shares == &BigUint::zero() {
+        return BigUint::zero();
+    }
+    shares.clone() * pool.clone() / total_shares.clone()
+}
+
+/// Shares burned when withdrawing `amount` from the pool, rounded up so that a depositor
+/// can never withdraw more than its claim.
+pub fn shares_for_withdrawal<BigUint: BigUintApi>(
+    amount: &BigUint,
+    pool: &BigUint,
+    total_shares: &BigUint,
+) -> BigUint {
+    if pool == &BigUint::zero() {
+        return BigUint::zero();
+    }
+    let numerator = amount.clone() * total_shares.clone();
+    let shares = numerator.clone() / pool.clone();
+    if shares.clone() * pool.clone() < numerator {
+        shares + BigUint::from(1u64)
+    } else {
+        shares
+    }
+}
@@ -0,0 +1,67 @@
+elrond_wasm::imports!();
+use crate::aggregator_interface::Submission;
+
+/// Thresholds are expressed as fractions of this value (1% == 1_000)
+pub const THRESHOLD_MULTIPLIER: u64 = 100_000;
+
+/// Checks if any of the values in the current Submission deviates from the corresponding
+/// value in the previous Submission by more than the given threshold. A change of sign
+/// always counts as a deviation, unless the previous value is zero.
+pub fn submission_exceeds_threshold<BigUint: BigUintApi>(
+    previous: &Submission<BigUint>,
+    current: &Submission<BigUint>,
+    threshold: u64,
+) -> bool {
+    previous
+        .values
+        .iter()
+        .zip(current.values.iter())
+        .enumerate()
+        .any(|(index, (previous_value, current_value))| {
+            if previous.is_negative(index) != current.is_negative(index) {
+                return previous_value != &BigUint::zero();
+            }
+            exceeds_threshold(previous_value, current_value, threshold)
+        })
+}
+
+/// Checks if the spread of the submissions for any of the values of an answer is greater
+/// than `max_dispersion`, relative to the magnitude of that value. As with deviations,
+/// a value of zero is never considered dispersed; 0 disables the check.
+pub fn dispersion_exceeds_band<BigUint: BigUintApi>(
+    answer: &Submission<BigUint>,
+    dispersion: &[BigUint],
+    max_dispersion: u64,
+) -> bool {
+    if max_dispersion == 0 {
+        return false;
+    }
+    answer
+        .values
+        .iter()
+        .zip(dispersion.iter())
+        .any(|(value, spread)| {
+            value != &BigUint::zero()
+                && spread.clone() * BigUint::from(THRESHOLD_MULTIPLIER) / value.clone()
+                    > BigUint::from(max_dispersion)
+        })
+}
+
+/// Returns true if the relative change between the two values is strictly greater than
+/// the threshold. A previous value of zero never counts as a deviation, since the relative
+/// change cannot be computed.
+pub fn exceeds_threshold<BigUint: BigUintApi>(
+    previous: &BigUint,
+    current: &BigUint,
+    threshold: u64,
+) -> bool {
+    if previous == &BigUint::zero() {
+        return false;
+    }
+    let change = if current > previous {
+        current.clone() - previous.clone()
+    } else {
+        previous.clone() - current.clone()
+    };
+    change * BigUint::from(THRESHOLD_MULTIPLIER) / previous.clone() > BigUint::from(threshold)
+}
@@ -0,0 +1,304 @@
+//! The layouts of the stored data used by previous storage versions, which the upgrade
+//! reads back to convert them. Each layout is named after the version which introduced it.
+
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use crate::aggregator_data::{FeedConfig, OracleStatus, RoundDetails, RoundSubmission};
+use crate::aggregator_interface::{Round, Submission};
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct RoundV0<BigUint: BigUintApi> {
+    pub round_id: u64,
+    pub answer: Option<SubmissionV1<BigUint>>,
+    pub decimals: u8,
+    pub description: BoxedBytes,
+    pub started_at: u64,
+    pub updated_at: u64,
+    pub answered_in_round: u64,
+}
+
+/// The dispersion of the rounds answered before it was recorded is unknown, and left empty.
+impl<BigUint: BigUintApi> From<RoundV0<BigUint>> for RoundV1<BigUint> {
+    fn from(round: RoundV0<BigUint>) -> Self {
+        RoundV1 {
+            round_id: round.round_id,
+            answer: round.answer,
+            decimals: round.decimals,
+            description: round.description,
+            started_at: round.started_at,
+            updated_at: round.updated_at,
+            answered_in_round: round.answered_in_round,
+            dispersion: Vec::new(),
+        }
+    }
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct RoundDetailsV0<BigUint: BigUintApi> {
+    pub submissions: Vec<SubmissionV1<BigUint>>,
+    pub max_submissions: u64,
+    pub min_submissions: u64,
+    pub timeout: u64,
+    pub payment_amount: BigUint,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct OracleStatusV0<BigUint: BigUintApi> {
+    pub withdrawable: BigUint,
+    pub starting_round: u64,
+    pub ending_round: u64,
+    pub last_reported_round: u64,
+    pub last_started_round: u64,
+    pub latest_submission: Option<SubmissionV1<BigUint>>,
+    pub admin: Address,
+    pub pending_admin: Option<Address>,
+}
+
+/// The withdrawable payments are stored apart from the status since version 1.
+impl<BigUint: BigUintApi> From<OracleStatusV0<BigUint>> for OracleStatusV1<BigUint> {
+    fn from(status: OracleStatusV0<BigUint>) -> Self {
+        OracleStatusV1 {
+            starting_round: status.starting_round,
+            ending_round: status.ending_round,
+            last_reported_round: status.last_reported_round,
+            last_started_round: status.last_started_round,
+            latest_submission: status.latest_submission,
+            admin: status.admin,
+            pending_admin: status.pending_admin,
+            payout_address: None,
+        }
+    }
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct SubmissionV1<BigUint: BigUintApi> {
+    pub values: Vec<BigUint>,
+}
+
+impl<BigUint: BigUintApi> From<SubmissionV1<BigUint>> for Submission<BigUint> {
+    fn from(submission: SubmissionV1<BigUint>) -> Self {
+        Submission::from_values(submission.values)
+    }
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct WeightedSubmissionV1<BigUint: BigUintApi> {
+    pub submission: SubmissionV1<BigUint>,
+    pub weight: u64,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct RoundSubmissionV2<BigUint: BigUintApi> {
+    pub oracle: Address,
+    pub submission: SubmissionV1<BigUint>,
+    pub timestamp: u64,
+}
+
+impl<BigUint: BigUintApi> From<RoundSubmissionV2<BigUint>> for RoundSubmission<BigUint> {
+    fn from(submission: RoundSubmissionV2<BigUint>) -> Self {
+        RoundSubmission {
+            oracle: submission.oracle,
+            submission: submission.submission.into(),
+            timestamp: submission.timestamp,
+        }
+    }
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct RoundV1<BigUint: BigUintApi> {
+    pub round_id: u64,
+    pub answer: Option<SubmissionV1<BigUint>>,
+    pub decimals: u8,
+    pub description: BoxedBytes,
+    pub started_at: u64,
+    pub updated_at: u64,
+    pub answered_in_round: u64,
+    pub dispersion: Vec<BigUint>,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct RoundV3<BigUint: BigUintApi> {
+    pub round_id: u64,
+    pub answer: Option<Submission<BigUint>>,
+    pub decimals: u8,
+    pub description: BoxedBytes,
+    pub started_at: u64,
+    pub updated_at: u64,
+    pub answered_in_round: u64,
+    pub dispersion: Vec<BigUint>,
+}
+
+impl<BigUint: BigUintApi> From<RoundV1<BigUint>> for RoundV3<BigUint> {
+    fn from(round: RoundV1<BigUint>) -> Self {
+        RoundV3 {
+            round_id: round.round_id,
+            answer: round.answer.map(Submission::from),
+            decimals: round.decimals,
+            description: round.description,
+            started_at: round.started_at,
+            updated_at: round.updated_at,
+            answered_in_round: round.answered_in_round,
+            dispersion: round.dispersion,
+        }
+    }
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct RoundV5<BigUint: BigUintApi> {
+    pub round_id: u64,
+    pub answer: Option<Submission<BigUint>>,
+    pub decimals: u8,
+    pub description: BoxedBytes,
+    pub started_at: u64,
+    pub updated_at: u64,
+    pub answered_in_round: u64,
+    pub dispersion: Vec<BigUint>,
+    pub min_observation_age: u64,
+    pub median_observation_age: u64,
+    pub max_observation_age: u64,
+}
+
+/// The observation ages of the rounds answered before they were recorded are unknown,
+/// and left at 0.
+impl<BigUint: BigUintApi> From<RoundV3<BigUint>> for RoundV5<BigUint> {
+    fn from(round: RoundV3<BigUint>) -> Self {
+        RoundV5 {
+            round_id: round.round_id,
+            answer: round.answer,
+            decimals: round.decimals,
+            description: round.description,
+            started_at: round.started_at,
+            updated_at: round.updated_at,
+            answered_in_round: round.answered_in_round,
+            dispersion: round.dispersion,
+            min_observation_age: 0,
+            median_observation_age: 0,
+            max_observation_age: 0,
+        }
+    }
+}
+
+/// A round is fresh when it was answered by its own submissions, rather than carrying
+/// the answer of a previous round.
+impl<BigUint: BigUintApi> From<RoundV5<BigUint>> for Round<BigUint> {
+    fn from(round: RoundV5<BigUint>) -> Self {
+        let fresh = round.answer.is_some() && round.answered_in_round == round.round_id;
+        Round {
+            round_id: round.round_id,
+            answer: round.answer,
+            decimals: round.decimals,
+            description: round.description,
+            started_at: round.started_at,
+            updated_at: round.updated_at,
+            answered_in_round: round.answered_in_round,
+            dispersion: round.dispersion,
+            min_observation_age: round.min_observation_age,
+            median_observation_age: round.median_observation_age,
+            max_observation_age: round.max_observation_age,
+            fresh,
+        }
+    }
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct OracleStatusV1<BigUint: BigUintApi> {
+    pub starting_round: u64,
+    pub ending_round: u64,
+    pub last_reported_round: u64,
+    pub last_started_round: u64,
+    pub latest_submission: Option<SubmissionV1<BigUint>>,
+    pub admin: Address,
+    pub pending_admin: Option<Address>,
+    pub payout_address: Option<Address>,
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct OracleStatusV3<BigUint: BigUintApi> {
+    pub starting_round: u64,
+    pub ending_round: u64,
+    pub last_reported_round: u64,
+    pub last_started_round: u64,
+    pub latest_submission: Option<Submission<BigUint>>,
+    pub admin: Address,
+    pub pending_admin: Option<Address>,
+    pub payout_address: Option<Address>,
+}
+
+impl<BigUint: BigUintApi> From<OracleStatusV1<BigUint>> for OracleStatusV3<BigUint> {
+    fn from(status: OracleStatusV1<BigUint>) -> Self {
+        OracleStatusV3 {
+            starting_round: status.starting_round,
+            ending_round: status.ending_round,
+            last_reported_round: status.last_reported_round,
+            last_started_round: status.last_started_round,
+            latest_submission: status.latest_submission.map(Submission::from),
+            admin: status.admin,
+            pending_admin: status.pending_admin,
+            payout_address: status.payout_address,
+        }
+    }
+}
+
+impl<BigUint: BigUintApi> From<OracleStatusV3<BigUint>> for OracleStatus<BigUint> {
+    fn from(status: OracleStatusV3<BigUint>) -> Self {
+        OracleStatus {
+            starting_round: status.starting_round,
+            ending_round: status.ending_round,
+            last_reported_round: status.last_reported_round,
+            last_started_round: status.last_started_round,
+            latest_submission: status.latest_submission,
+            admin: status.admin,
+            pending_admin: status.pending_admin,
+            payout_address: status.payout_address,
+            restart_delay: None,
+        }
+    }
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct RoundDetailsV1<BigUint: BigUintApi> {
+    pub max_submissions: u64,
+    pub min_submissions: u64,
+    pub timeout: u64,
+    pub payment_amount: BigUint,
+    pub payment_token: TokenIdentifier,
+    pub bonus_submissions: u64,
+    pub bonus_amount: BigUint,
+    pub values_count: usize,
+}
+
+/// Rounds started before signed values were supported only accept non-negative values.
+impl<BigUint: BigUintApi> From<RoundDetailsV1<BigUint>> for RoundDetails<BigUint> {
+    fn from(details: RoundDetailsV1<BigUint>) -> Self {
+        RoundDetails {
+            max_submissions: details.max_submissions,
+            min_submissions: details.min_submissions,
+            timeout: details.timeout,
+            payment_amount: details.payment_amount,
+            payment_token: details.payment_token,
+            bonus_submissions: details.bonus_submissions,
+            bonus_amount: details.bonus_amount,
+            values_count: details.values_count,
+            value_offset: BigUint::zero(),
+        }
+    }
+}
+
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct FeedConfigV1 {
+    pub decimals: u8,
+    pub description: BoxedBytes,
+    pub values_count: usize,
+}
+
+impl From<FeedConfigV1> for FeedConfig {
+    fn from(config: FeedConfigV1) -> Self {
+        FeedConfig {
+            decimals: config.decimals,
+            description: config.description,
+            values_count: config.values_count,
+            value_decimals: Vec::new(),
+        }
+    }
+}
@@ -4,19 +4,134 @@
 elrond_wasm::imports!();
 mod aggregator_data;
 pub mod aggregator_interface;
+pub mod deposits;
+pub mod deviation;
+mod legacy_data;
 pub mod median;
+pub mod report;
+pub mod rotation;
+pub mod scaling;
+pub mod twap;
+pub mod type_and_version;
 
-use aggregator_data::{Funds, OracleRoundState, OracleStatus, Requester, RoundDetails, Submission};
-use aggregator_interface::Round;
+use aggregator_data::{
+    EmergencyWithdrawal, EscrowedPayment, FeedConfig, Funds, FutureRoundsProposal,
+    OracleChangeProposal, OracleConfigsProposal, OracleRoundState, OracleStake, OracleStats,
+    OracleStatus, PaymentTokenProposal, PendingEarnings, Requester, RoundDetails,
+    RoundSubmission, Submission, SubmissionBounds, Subscriber, TokenMigrationProposal,
+};
+use aggregator_interface::{
+    AggregationStrategy, AggregatorConfig, FeedInfo, OracleConfig, ReportObservation, Round,
+    TimeoutPolicy,
+};
+use legacy_data::{
+    FeedConfigV1, OracleStatusV0, OracleStatusV1, OracleStatusV3, RoundDetailsV0, RoundDetailsV1,
+    RoundSubmissionV2, RoundV0, RoundV1, RoundV3, RoundV5, WeightedSubmissionV1,
+};
 
-const RESERVE_ROUNDS: u64 = 2;
+const DEFAULT_RESERVE_ROUNDS: u64 = 2;
+const MAX_RESERVE_ROUNDS: u64 = 10;
 const ROUND_MAX: u64 = u64::MAX;
+const MAX_ROUND_DATA_RANGE: u64 = 100;
+const FLAG_RAISING_GAS_LIMIT: u64 = 10_000_000;
+const MAX_AUTO_PRUNED_ROUNDS: u64 = 5;
+const DEFAULT_TRIM_PERCENT: u8 = 25;
+const DEFAULT_ORACLE_WEIGHT: u64 = 1;
+const DEFAULT_EMERGENCY_WITHDRAW_DELAY: u64 = 2 * 24 * 60 * 60;
+const STORAGE_VERSION: u32 = 8;
+const MAX_MIGRATED_ITEMS: usize = 50;
+const MAX_SUBSCRIBERS: usize = 10;
+const MAX_NOTIFICATION_GAS_LIMIT: u64 = 20_000_000;
+const DEFAULT_NOTIFICATION_GAS_PRICE: u64 = 1_000_000_000;
+
+/// Requires the caller to be the owner or, once governors are configured, a governor
+/// executing an action which enough governors have approved. An action is identified by
+/// the keccak256 hash of the endpoint name followed by its nested encoded arguments.
+macro_rules! only_governance {
+    ($trait_self:expr, $endpoint:expr $(, $arg:expr)* $(,)?) => {
+        #[allow(unused_mut)]
+        let mut action: Vec<u8> = $endpoint.to_vec();
+        $(
+            if elrond_wasm::elrond_codec::NestedEncode::dep_encode(&$arg, &mut action).is_err() {
+                return sc_error!("cannot encode action");
+            }
+        )*
+        $trait_self.require_governance(&action)?;
+    };
+}
 
 #[elrond_wasm_derive::contract]
 pub trait Aggregator {
+    #[view(getTokenId)]
     #[storage_mapper("token_id")]
     fn token_id(&self) -> SingleValueMapper<Self::Storage, TokenIdentifier>;
 
+    /// The layout of the stored data; 0 for feeds deployed before it was versioned.
+    #[view(getStorageVersion)]
+    #[storage_mapper("storage_version")]
+    fn storage_version(&self) -> SingleValueMapper<Self::Storage, u32>;
+
+    /// The number of rounds, oracles and deposits already converted by the migration step
+    /// in progress, from which `migrateStorage` continues it.
+    #[view(getStorageMigrationProgress)]
+    #[storage_mapper("storage_migration_progress")]
+    fn storage_migration_progress(&self) -> SingleValueMapper<Self::Storage, usize>;
+
+    // The stored data in the layouts of previous storage versions, read by the upgrade
+
+    #[storage_mapper("rounds")]
+    fn rounds_v0(&self) -> MapMapper<Self::Storage, u64, RoundV0<Self::BigUint>>;
+
+    #[storage_mapper("oracles")]
+    fn oracles_v0(&self) -> MapMapper<Self::Storage, Address, OracleStatusV0<Self::BigUint>>;
+
+    #[storage_mapper("details")]
+    fn details_v0(&self) -> MapMapper<Self::Storage, u64, RoundDetailsV0<Self::BigUint>>;
+
+    #[storage_mapper("recorded_funds")]
+    fn recorded_funds_v0(&self) -> SingleValueMapper<Self::Storage, Funds<Self::BigUint>>;
+
+    #[storage_mapper("deposits")]
+    fn deposits_v0(&self) -> MapMapper<Self::Storage, Address, Self::BigUint>;
+
+    #[storage_mapper("rounds")]
+    fn rounds_v1(&self) -> MapMapper<Self::Storage, u64, RoundV1<Self::BigUint>>;
+
+    #[storage_mapper("rounds")]
+    fn rounds_v3(&self) -> MapMapper<Self::Storage, u64, RoundV3<Self::BigUint>>;
+
+    #[storage_mapper("rounds")]
+    fn rounds_v5(&self) -> MapMapper<Self::Storage, u64, RoundV5<Self::BigUint>>;
+
+    #[storage_mapper("oracles")]
+    fn oracles_v1(&self) -> MapMapper<Self::Storage, Address, OracleStatusV1<Self::BigUint>>;
+
+    #[storage_mapper("oracles")]
+    fn oracles_v3(&self) -> MapMapper<Self::Storage, Address, OracleStatusV3<Self::BigUint>>;
+
+    #[storage_mapper("round_submissions")]
+    fn round_submissions_v1(
+        &self,
+        round_id: u64,
+    ) -> VecMapper<Self::Storage, WeightedSubmissionV1<Self::BigUint>>;
+
+    #[storage_mapper("round_submissions")]
+    fn round_submissions_v2(
+        &self,
+        round_id: u64,
+    ) -> VecMapper<Self::Storage, RoundSubmissionV2<Self::BigUint>>;
+
+    #[storage_mapper("details")]
+    fn details_v1(&self) -> MapMapper<Self::Storage, u64, RoundDetailsV1<Self::BigUint>>;
+
+    #[storage_mapper("pending_feed_config")]
+    fn pending_feed_config_v1(&self) -> SingleValueMapper<Self::Storage, FeedConfigV1>;
+
+    #[view(typeAndVersion)]
+    fn type_and_version(&self) -> BoxedBytes {
+        type_and_version::type_and_version("Aggregator", env!("CARGO_PKG_VERSION"))
+    }
+
     // Round related params
     #[storage_mapper("payment_amount")]
     fn payment_amount(&self) -> SingleValueMapper<Self::Storage, Self::BigUint>;
@@ -39,6 +154,17 @@ pub trait Aggregator {
     #[storage_mapper("max_submission_value")]
     fn max_submission_value(&self) -> SingleValueMapper<Self::Storage, Self::BigUint>;
 
+    /// Negative values down to `-negative_value_bound` are accepted in the rounds started
+    /// while it is set; 0 only accepts non-negative values.
+    #[view(getNegativeValueBound)]
+    #[storage_mapper("negative_value_bound")]
+    fn negative_value_bound(&self) -> SingleValueMapper<Self::Storage, Self::BigUint>;
+
+    #[storage_mapper("pending_submission_bounds")]
+    fn pending_submission_bounds(
+        &self,
+    ) -> SingleValueMapper<Self::Storage, SubmissionBounds<Self::BigUint>>;
+
     #[storage_mapper("reporting_round_id")]
     fn reporting_round_id(&self) -> SingleValueMapper<Self::Storage, u64>;
 
@@ -51,6 +177,35 @@ pub trait Aggregator {
     #[storage_mapper("rounds")]
     fn rounds(&self) -> MapMapper<Self::Storage, u64, Round<Self::BigUint>>;
 
+    /// The submissions of a round, stored apart from its details so that every submission
+    /// only appends a single entry. They are kept until the round is pruned.
+    #[storage_mapper("round_submissions")]
+    fn round_submissions(
+        &self,
+        round_id: u64,
+    ) -> VecMapper<Self::Storage, RoundSubmission<Self::BigUint>>;
+
+    /// When enabled, the payments of a round are held until the round is answered.
+    #[view(getEscrowPayments)]
+    #[storage_mapper("escrow_payments")]
+    fn escrow_payments(&self) -> SingleValueMapper<Self::Storage, bool>;
+
+    /// The payments held for the oracles which submitted to a round not answered yet.
+    #[storage_mapper("escrowed_payments")]
+    fn escrowed_payments(
+        &self,
+        round_id: u64,
+    ) -> VecMapper<Self::Storage, EscrowedPayment<Self::BigUint>>;
+
+    /// The values submitted for one of the values of an open round, with the weight of
+    /// their oracle, kept sorted as they arrive.
+    #[storage_mapper("sorted_submission_values")]
+    fn sorted_submission_values(
+        &self,
+        round_id: u64,
+        value_index: usize,
+    ) -> SingleValueMapper<Self::Storage, Vec<(Self::BigUint, u64)>>;
+
     #[storage_mapper("details")]
     fn details(&self) -> MapMapper<Self::Storage, u64, RoundDetails<Self::BigUint>>;
 
@@ -58,10 +213,55 @@ pub trait Aggregator {
     fn requesters(&self) -> MapMapper<Self::Storage, Address, Requester>;
 
     #[storage_mapper("recorded_funds")]
-    fn recorded_funds(&self) -> SingleValueMapper<Self::Storage, Funds<Self::BigUint>>;
+    fn recorded_funds(
+        &self,
+        token_id: &TokenIdentifier,
+    ) -> SingleValueMapper<Self::Storage, Funds<Self::BigUint>>;
 
-    #[storage_mapper("deposits")]
-    fn deposits(&self) -> MapMapper<Self::Storage, Address, Self::BigUint>;
+    /// Each depositor owns a share of the available funds of a token, which shrink as
+    /// the oracles are paid.
+    #[storage_mapper("deposit_shares")]
+    fn deposit_shares(
+        &self,
+        token_id: &TokenIdentifier,
+    ) -> MapMapper<Self::Storage, Address, Self::BigUint>;
+
+    #[storage_mapper("total_deposit_shares")]
+    fn total_deposit_shares(
+        &self,
+        token_id: &TokenIdentifier,
+    ) -> SingleValueMapper<Self::Storage, Self::BigUint>;
+
+    #[storage_mapper("oracle_withdrawable")]
+    fn oracle_withdrawable(
+        &self,
+        oracle: &Address,
+        token_id: &TokenIdentifier,
+    ) -> SingleValueMapper<Self::Storage, Self::BigUint>;
+
+    /// When enabled, the payments of each oracle are accumulated during an epoch and added
+    /// to its withdrawable balance once the epoch is over, saving storage writes.
+    #[view(getBatchedPayouts)]
+    #[storage_mapper("batched_payouts")]
+    fn batched_payouts(&self) -> SingleValueMapper<Self::Storage, bool>;
+
+    #[storage_mapper("pending_earnings")]
+    fn pending_earnings(
+        &self,
+        oracle: &Address,
+    ) -> SingleValueMapper<Self::Storage, PendingEarnings<Self::BigUint>>;
+
+    /// The payments owed to oracles which submitted while the available funds could not
+    /// cover them, settled as soon as funds are added.
+    #[storage_mapper("owed_payments")]
+    fn owed_payments(
+        &self,
+        token_id: &TokenIdentifier,
+    ) -> MapMapper<Self::Storage, Address, Self::BigUint>;
+
+    /// Tokens accepted for deposits besides the payment token of future rounds.
+    #[storage_mapper("accepted_tokens")]
+    fn accepted_tokens(&self) -> SetMapper<Self::Storage, TokenIdentifier>;
 
     #[storage_mapper("decimals")]
     fn decimals(&self) -> SingleValueMapper<Self::Storage, u8>;
@@ -72,6 +272,239 @@ pub trait Aggregator {
     #[storage_mapper("values_count")]
     fn values_count(&self) -> SingleValueMapper<Self::Storage, usize>;
 
+    /// The decimals of each value, for feeds whose values have different precisions;
+    /// empty when all the values use the feed's decimals.
+    #[storage_mapper("value_decimals")]
+    fn value_decimals(&self) -> SingleValueMapper<Self::Storage, Vec<u8>>;
+
+    #[storage_mapper("pending_feed_config")]
+    fn pending_feed_config(&self) -> SingleValueMapper<Self::Storage, FeedConfig>;
+
+    /// Consumer contracts notified of every new answer.
+    #[storage_mapper("subscribers")]
+    fn subscribers(&self) -> MapMapper<Self::Storage, Address, Subscriber<Self::BigUint>>;
+
+    /// Price of a unit of notification gas, in EGLD.
+    #[storage_mapper("notification_gas_price")]
+    fn notification_gas_price(&self) -> SingleValueMapper<Self::Storage, Self::BigUint>;
+
+    /// Notification fees owed to the callers which paid for the notifications' gas.
+    #[storage_mapper("notification_rewards")]
+    fn notification_rewards(
+        &self,
+        caller: &Address,
+    ) -> SingleValueMapper<Self::Storage, Self::BigUint>;
+
+    /// While enabled, only whitelisted contracts can read the answers on-chain.
+    #[view(getReadAccessCheckEnabled)]
+    #[storage_mapper("read_access_check_enabled")]
+    fn read_access_check_enabled(&self) -> SingleValueMapper<Self::Storage, bool>;
+
+    #[storage_mapper("read_access_list")]
+    fn read_access_list(&self) -> SetMapper<Self::Storage, Address>;
+
+    /// Fee for an epoch of read access, in the payment token; 0 disables paid access.
+    #[view(getReadFee)]
+    #[storage_mapper("read_fee")]
+    fn read_fee(&self) -> SingleValueMapper<Self::Storage, Self::BigUint>;
+
+    /// The first epoch in which the paid read access of a contract is no longer valid.
+    #[view(getReadAccessExpiry)]
+    #[storage_mapper("read_access_expiry")]
+    fn read_access_expiry(&self, reader: &Address) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[view(getFlagsAddress)]
+    #[storage_mapper("flags_address")]
+    fn flags_address(&self) -> SingleValueMapper<Self::Storage, Address>;
+
+    #[view(getFlaggingThreshold)]
+    #[storage_mapper("flagging_threshold")]
+    fn flagging_threshold(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[view(getMaxSubmissionDeviation)]
+    #[storage_mapper("max_submission_deviation")]
+    fn max_submission_deviation(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[view(getRejectDeviatingSubmissions)]
+    #[storage_mapper("reject_deviating_submissions")]
+    fn reject_deviating_submissions(&self) -> SingleValueMapper<Self::Storage, bool>;
+
+    #[view(getMaxDispersion)]
+    #[storage_mapper("max_dispersion")]
+    fn max_dispersion(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    /// Rounds whose latest computed answer was withheld because the submissions were
+    /// too dispersed
+    #[storage_mapper("disputed_rounds")]
+    fn disputed_rounds(&self) -> SetMapper<Self::Storage, u64>;
+
+    #[storage_mapper("timeout_policy")]
+    fn timeout_policy(&self) -> SingleValueMapper<Self::Storage, TimeoutPolicy>;
+
+    /// Rounds left without an answer when they timed out, under the `MarkStale` and
+    /// `Halt` policies
+    #[storage_mapper("stale_rounds")]
+    fn stale_rounds(&self) -> SetMapper<Self::Storage, u64>;
+
+    /// Set when a round times out under the `Halt` policy
+    #[view(isHalted)]
+    #[storage_mapper("halted")]
+    fn halted(&self) -> SingleValueMapper<Self::Storage, bool>;
+
+    /// The maximum number of rounds started since the latest answered round; 0 disables
+    /// the limit.
+    #[view(getMaxUnansweredRounds)]
+    #[storage_mapper("max_unanswered_rounds")]
+    fn max_unanswered_rounds(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[view(getHeartbeat)]
+    #[storage_mapper("heartbeat")]
+    fn heartbeat(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[view(getStaleRoundIncentive)]
+    #[storage_mapper("stale_round_incentive")]
+    fn stale_round_incentive(&self) -> SingleValueMapper<Self::Storage, Self::BigUint>;
+
+    #[view(getBonusSubmissions)]
+    #[storage_mapper("bonus_submissions")]
+    fn bonus_submissions(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[view(getBonusAmount)]
+    #[storage_mapper("bonus_amount")]
+    fn bonus_amount(&self) -> SingleValueMapper<Self::Storage, Self::BigUint>;
+
+    #[view(getRoundRetention)]
+    #[storage_mapper("round_retention")]
+    fn round_retention(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[view(getOldestRoundId)]
+    #[storage_mapper("oldest_round_id")]
+    fn oldest_round_id(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[storage_mapper("aggregation_strategy")]
+    fn aggregation_strategy(&self) -> SingleValueMapper<Self::Storage, AggregationStrategy>;
+
+    #[storage_mapper("oracle_weights")]
+    fn oracle_weights(&self) -> MapMapper<Self::Storage, Address, u64>;
+
+    /// The number of oracles selected each epoch out of the enabled oracles to submit
+    /// during that epoch; 0 lets all the enabled oracles submit.
+    #[view(getCommitteeSize)]
+    #[storage_mapper("committee_size")]
+    fn committee_size(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[storage_mapper("committee")]
+    fn committee(&self) -> SetMapper<Self::Storage, Address>;
+
+    /// The epoch the committee was selected for; empty when it has to be selected again.
+    #[storage_mapper("committee_epoch")]
+    fn committee_epoch(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    /// The minimum number of seconds between two submissions of the same oracle;
+    /// 0 disables the limit.
+    #[view(getMinSubmissionInterval)]
+    #[storage_mapper("min_submission_interval")]
+    fn min_submission_interval(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[view(getOracleLastSubmission)]
+    #[storage_mapper("oracle_last_submission")]
+    fn oracle_last_submission(&self, oracle: &Address) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[storage_mapper("stake_token_id")]
+    fn stake_token_id(&self) -> SingleValueMapper<Self::Storage, TokenIdentifier>;
+
+    #[storage_mapper("min_stake")]
+    fn min_stake(&self) -> SingleValueMapper<Self::Storage, Self::BigUint>;
+
+    #[storage_mapper("unbonding_period")]
+    fn unbonding_period(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[storage_mapper("oracle_stakes")]
+    fn oracle_stakes(
+        &self,
+        oracle: &Address,
+    ) -> SingleValueMapper<Self::Storage, OracleStake<Self::BigUint>>;
+
+    #[storage_mapper("oracle_stats")]
+    fn oracle_stats(&self, oracle: &Address) -> SingleValueMapper<Self::Storage, OracleStats>;
+
+    /// The latest round counted in the liveness statistics of the oracle
+    #[storage_mapper("oracle_liveness_round")]
+    fn oracle_liveness_round(&self, oracle: &Address) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[storage_mapper("oracle_payment_overrides")]
+    fn oracle_payment_overrides(&self) -> MapMapper<Self::Storage, Address, Self::BigUint>;
+
+    #[storage_mapper("trim_percent")]
+    fn trim_percent(&self) -> SingleValueMapper<Self::Storage, u8>;
+
+    #[storage_mapper("reserve_rounds")]
+    fn reserve_rounds(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[storage_mapper("signing_keys")]
+    fn signing_keys(&self) -> MapMapper<Self::Storage, Address, BoxedBytes>;
+
+    /// The addresses allowed to submit on behalf of an oracle
+    #[storage_mapper("delegates")]
+    fn delegates(&self, oracle: &Address) -> SetMapper<Self::Storage, Address>;
+
+    /// The oracle each delegate submits for
+    #[storage_mapper("delegated_oracles")]
+    fn delegated_oracles(&self) -> MapMapper<Self::Storage, Address, Address>;
+
+    #[view(isPaused)]
+    #[storage_mapper("paused")]
+    fn paused(&self) -> SingleValueMapper<Self::Storage, bool>;
+
+    /// When set, `updateFutureRounds`, `changeOracles`, `configureOracles`,
+    /// `setPaymentToken` and `migratePaymentToken` have to be proposed and can only be
+    /// executed after this delay.
+    #[view(getConfigTimelock)]
+    #[storage_mapper("config_timelock")]
+    fn config_timelock(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[storage_mapper("future_rounds_proposal")]
+    fn future_rounds_proposal(
+        &self,
+    ) -> SingleValueMapper<Self::Storage, FutureRoundsProposal<Self::BigUint>>;
+
+    #[storage_mapper("oracle_change_proposal")]
+    fn oracle_change_proposal(&self) -> SingleValueMapper<Self::Storage, OracleChangeProposal>;
+
+    #[storage_mapper("oracle_configs_proposal")]
+    fn oracle_configs_proposal(
+        &self,
+    ) -> SingleValueMapper<Self::Storage, OracleConfigsProposal<Self::BigUint>>;
+
+    #[storage_mapper("payment_token_proposal")]
+    fn payment_token_proposal(
+        &self,
+    ) -> SingleValueMapper<Self::Storage, PaymentTokenProposal<Self::BigUint>>;
+
+    #[storage_mapper("token_migration_proposal")]
+    fn token_migration_proposal(&self) -> SingleValueMapper<Self::Storage, TokenMigrationProposal>;
+
+    #[storage_mapper("governors")]
+    fn governors(&self) -> SetMapper<Self::Storage, Address>;
+
+    #[view(getApprovalThreshold)]
+    #[storage_mapper("approval_threshold")]
+    fn approval_threshold(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[storage_mapper("action_approvals")]
+    fn action_approvals(&self, action_hash: &H256) -> SetMapper<Self::Storage, Address>;
+
+    #[storage_mapper("emergency_withdraw_delay")]
+    fn emergency_withdraw_delay(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[storage_mapper("pending_emergency_withdrawal")]
+    fn pending_emergency_withdrawal(
+        &self,
+    ) -> SingleValueMapper<Self::Storage, EmergencyWithdrawal<Self::BigUint>>;
+
+    /// Also called when the contract code is upgraded. An upgrade keeps the state of the
+    /// feed, ignores the arguments and migrates the stored data to the current layout, as
+    /// far as `MAX_MIGRATED_ITEMS` allows; `migrateStorage` continues the rest.
     #[init]
     fn init(
         &self,
@@ -83,9 +516,16 @@ pub trait Aggregator {
         decimals: u8,
         description: BoxedBytes,
         values_count: usize,
+        #[var_args] aggregation_strategy: OptionalArg<AggregationStrategy>,
+        #[var_args] value_decimals: OptionalArg<Vec<u8>>,
     ) -> SCResult<()> {
+        if !self.token_id().is_empty() {
+            self.migrate_storage(MAX_MIGRATED_ITEMS)?;
+            return Ok(());
+        }
+        self.storage_version().set(&STORAGE_VERSION);
         self.token_id().set(&token_id);
-        self.recorded_funds().set(&Funds {
+        self.recorded_funds(&token_id).set(&Funds {
             available: Self::BigUint::zero(),
             allocated: Self::BigUint::zero(),
         });
@@ -96,10 +536,328 @@ pub trait Aggregator {
         self.decimals().set(&decimals);
         self.description().set(&description);
         self.values_count().set(&values_count);
+        let value_decimals = value_decimals.into_option().unwrap_or_default();
+        self.require_valid_value_decimals(&value_decimals, values_count)?;
+        self.value_decimals().set(&value_decimals);
+        if let OptionalArg::Some(strategy) = aggregation_strategy {
+            self.aggregation_strategy().set(&strategy);
+        }
         self.initialize_new_round(&0)?;
         Ok(())
     }
 
+    /// Continues the migration of the stored data when it did not fit in the upgrade,
+    /// converting at most `max_items` rounds, oracles or deposits. The feed behaves as
+    /// paused until it is done. Returns whether the stored data reached the current layout.
+    #[endpoint(migrateStorage)]
+    fn continue_storage_migration(&self, max_items: usize) -> SCResult<bool> {
+        require!(max_items > 0, "max_items must be positive");
+        self.migrate_storage(max_items)
+    }
+
+    fn migrate_storage(&self, max_items: usize) -> SCResult<bool> {
+        let mut version = self.storage_version().get();
+        require!(
+            version <= STORAGE_VERSION,
+            "cannot downgrade the storage version"
+        );
+        let mut remaining = max_items;
+        while version < STORAGE_VERSION {
+            let progress = self.storage_migration_progress().get();
+            let migrated = self.migrate_storage_from(version, progress, remaining)?;
+            if migrated == remaining {
+                // the step may have items left, which the next call converts
+                self.storage_migration_progress()
+                    .set(&(progress + migrated));
+                break;
+            }
+            remaining -= migrated;
+            self.storage_migration_progress().clear();
+            version += 1;
+        }
+        self.storage_version().set(&version);
+        Ok(version == STORAGE_VERSION)
+    }
+
+    fn require_storage_migrated(&self) -> SCResult<()> {
+        require!(
+            self.storage_version().get() == STORAGE_VERSION,
+            "storage migration in progress"
+        );
+        Ok(())
+    }
+
+    /// Converts the stored data from the given layout version towards the next one,
+    /// skipping the `start` items already converted and converting at most `max_items`.
+    /// Returns the number of items converted; fewer than `max_items` once the step is done.
+    fn migrate_storage_from(
+        &self,
+        version: u32,
+        start: usize,
+        max_items: usize,
+    ) -> SCResult<usize> {
+        let migrated = match version {
+            0 => self.migrate_unversioned_layout(start, max_items),
+            1 => self.migrate_round_submission_oracles(start, max_items),
+            2 => self.migrate_submission_signs(start, max_items),
+            3 => self.migrate_value_decimals(),
+            4 => self.migrate_observation_ages(start, max_items),
+            5 => self.migrate_oracle_restart_delays(start, max_items),
+            6 => self.migrate_fresh_flags(start, max_items),
+            7 => self.migrate_oracle_liveness_rounds(start, max_items),
+            _ => return sc_error!("unknown storage version"),
+        };
+        Ok(migrated)
+    }
+
+    /// Version 1 stores the funds, the deposits and the withdrawable payments per token,
+    /// and the submissions of a round apart from its details. The funds are moved to the
+    /// payment token, and every token deposited becomes a share of the available funds.
+    fn migrate_unversioned_layout(&self, start: usize, max_items: usize) -> usize {
+        let token_id = self.token_id().get();
+        if !self.recorded_funds_v0().is_empty() {
+            self.recorded_funds(&token_id)
+                .set(&self.recorded_funds_v0().get());
+            self.recorded_funds_v0().clear();
+        }
+        let mut position = start;
+        let round_ids: Vec<u64> = self
+            .rounds_v0()
+            .keys()
+            .skip(position)
+            .take(max_items)
+            .collect();
+        for round_id in round_ids.iter() {
+            if let Some(round) = self.rounds_v0().get(round_id) {
+                self.rounds_v1().insert(*round_id, round.into());
+            }
+        }
+        let mut migrated = round_ids.len();
+        position = position.saturating_sub(self.rounds_v0().len());
+
+        let values_count = self.values_count().get();
+        let round_ids: Vec<u64> = self
+            .details_v0()
+            .keys()
+            .skip(position)
+            .take(max_items - migrated)
+            .collect();
+        for round_id in round_ids.iter() {
+            self.migrate_unversioned_round_details(*round_id, &token_id, values_count);
+        }
+        migrated += round_ids.len();
+        position = position.saturating_sub(self.details_v0().len());
+
+        let oracles: Vec<Address> = self
+            .oracles_v0()
+            .keys()
+            .skip(position)
+            .take(max_items - migrated)
+            .collect();
+        for oracle in oracles.iter() {
+            if let Some(status) = self.oracles_v0().get(oracle) {
+                self.oracle_withdrawable(oracle, &token_id)
+                    .set(&status.withdrawable);
+                self.oracles_v1().insert(oracle.clone(), status.into());
+            }
+        }
+        migrated += oracles.len();
+
+        // the converted deposits are removed, so the remaining ones always come first
+        let depositors: Vec<Address> = self
+            .deposits_v0()
+            .keys()
+            .take(max_items - migrated)
+            .collect();
+        for depositor in depositors.iter() {
+            if let Some(amount) = self.deposits_v0().remove(depositor) {
+                self.total_deposit_shares(&token_id)
+                    .update(|total_shares| *total_shares += &amount);
+                self.deposit_shares(&token_id)
+                    .insert(depositor.clone(), amount);
+            }
+        }
+        migrated + depositors.len()
+    }
+
+    /// The submissions kept in the details of the open rounds were not weighted, so they
+    /// get the default oracle weight.
+    fn migrate_unversioned_round_details(
+        &self,
+        round_id: u64,
+        token_id: &TokenIdentifier,
+        values_count: usize,
+    ) {
+        let details = match self.details_v0().get(&round_id) {
+            Some(details) => details,
+            None => return,
+        };
+        let mut sorted_values: Vec<Vec<(Self::BigUint, u64)>> =
+            (0..values_count).map(|_| Vec::new()).collect();
+        let mut submissions = self.round_submissions_v1(round_id);
+        for submission in details.submissions.into_iter() {
+            for (sorted, value) in sorted_values.iter_mut().zip(submission.values.iter()) {
+                median::insert_sorted(sorted, value.clone(), DEFAULT_ORACLE_WEIGHT);
+            }
+            submissions.push(&WeightedSubmissionV1 {
+                submission,
+                weight: DEFAULT_ORACLE_WEIGHT,
+            });
+        }
+        for (value_index, sorted) in sorted_values.iter().enumerate() {
+            self.sorted_submission_values(round_id, value_index)
+                .set(sorted);
+        }
+        self.details_v1().insert(
+            round_id,
+            RoundDetailsV1 {
+                max_submissions: details.max_submissions,
+                min_submissions: details.min_submissions,
+                timeout: details.timeout,
+                payment_amount: details.payment_amount,
+                payment_token: token_id.clone(),
+                bonus_submissions: 0,
+                bonus_amount: Self::BigUint::zero(),
+                values_count,
+            },
+        );
+    }
+
+    /// Version 2 records the oracle and the time of every submission. Only the open rounds
+    /// kept their submissions, which are matched to the oracles that last reported them;
+    /// their time was not recorded, so the start of the round is used instead.
+    fn migrate_round_submission_oracles(&self, start: usize, max_items: usize) -> usize {
+        let round_ids: Vec<u64> = self.details().keys().skip(start).take(max_items).collect();
+        for round_id in round_ids.iter().copied() {
+            let started_at = self
+                .rounds_v1()
+                .get(&round_id)
+                .map_or(0, |round| round.started_at);
+            let mut reporters: Vec<(Address, Vec<Self::BigUint>)> = self
+                .oracles_v1()
+                .iter()
+                .filter(|(_, status)| status.last_reported_round == round_id)
+                .filter_map(|(oracle, status)| {
+                    status
+                        .latest_submission
+                        .map(|submission| (oracle, submission.values))
+                })
+                .collect();
+            let submissions = self.round_submissions_v1(round_id);
+            for index in 1..=submissions.len() {
+                let submission = submissions.get(index).submission;
+                let oracle = reporters
+                    .iter()
+                    .position(|(_, values)| values == &submission.values)
+                    .map_or_else(Address::zero, |position| reporters.swap_remove(position).0);
+                self.round_submissions_v2(round_id).set(
+                    index,
+                    &RoundSubmissionV2 {
+                        oracle,
+                        submission,
+                        timestamp: started_at,
+                    },
+                );
+            }
+        }
+        round_ids.len()
+    }
+
+    /// Version 3 stores the signs of the submitted values, and the value offset of the
+    /// open rounds.
+    fn migrate_submission_signs(&self, start: usize, max_items: usize) -> usize {
+        let round_ids: Vec<u64> = self.rounds().keys().skip(start).take(max_items).collect();
+        for round_id in round_ids.iter().copied() {
+            if let Some(round) = self.rounds_v1().get(&round_id) {
+                self.rounds_v3().insert(round_id, round.into());
+            }
+            let submissions = self.round_submissions(round_id);
+            for index in 1..=submissions.len() {
+                let submission = self.round_submissions_v2(round_id).get(index);
+                submissions.set(index, &submission.into());
+            }
+            if let Some(details) = self.details_v1().get(&round_id) {
+                self.details().insert(round_id, details.into());
+            }
+        }
+        let oracles: Vec<Address> = self
+            .oracles()
+            .keys()
+            .skip(start.saturating_sub(self.rounds().len()))
+            .take(max_items - round_ids.len())
+            .collect();
+        for oracle in oracles.iter() {
+            if let Some(status) = self.oracles_v1().get(oracle) {
+                self.oracles_v3().insert(oracle.clone(), status.into());
+            }
+        }
+        round_ids.len() + oracles.len()
+    }
+
+    /// Version 4 stores the decimals of each value in the feed config.
+    fn migrate_value_decimals(&self) -> usize {
+        if !self.pending_feed_config_v1().is_empty() {
+            let config = self.pending_feed_config_v1().get();
+            self.pending_feed_config().set(&config.into());
+        }
+        0
+    }
+
+    /// Version 5 stores the observation ages of the rounds.
+    fn migrate_observation_ages(&self, start: usize, max_items: usize) -> usize {
+        let round_ids: Vec<u64> = self.rounds().keys().skip(start).take(max_items).collect();
+        for round_id in round_ids.iter() {
+            if let Some(round) = self.rounds_v3().get(round_id) {
+                self.rounds_v5().insert(*round_id, round.into());
+            }
+        }
+        round_ids.len()
+    }
+
+    /// Version 6 stores the restart delay override of the oracles.
+    fn migrate_oracle_restart_delays(&self, start: usize, max_items: usize) -> usize {
+        let oracles: Vec<Address> = self.oracles().keys().skip(start).take(max_items).collect();
+        for oracle in oracles.iter() {
+            if let Some(status) = self.oracles_v3().get(oracle) {
+                self.oracles().insert(oracle.clone(), status.into());
+            }
+        }
+        oracles.len()
+    }
+
+    /// Version 7 flags the rounds carrying the answer of a previous round.
+    fn migrate_fresh_flags(&self, start: usize, max_items: usize) -> usize {
+        let round_ids: Vec<u64> = self.rounds().keys().skip(start).take(max_items).collect();
+        for round_id in round_ids.iter() {
+            if let Some(round) = self.rounds_v5().get(round_id) {
+                self.rounds().insert(*round_id, round.into());
+            }
+        }
+        round_ids.len()
+    }
+
+    /// Version 8 counts the rounds an oracle was eligible for lazily, from the last round
+    /// counted for it. Every round before the reporting round was already counted, except
+    /// for the eligibility of the oracles which submitted to the reporting round.
+    fn migrate_oracle_liveness_rounds(&self, start: usize, max_items: usize) -> usize {
+        let reporting_round_id = self.reporting_round_id().get();
+        let oracles: Vec<Address> = self.oracles().keys().skip(start).take(max_items).collect();
+        for oracle in oracles.iter() {
+            let last_reported_round = self
+                .oracles()
+                .get(oracle)
+                .map_or(0, |oracle_status| oracle_status.last_reported_round);
+            if last_reported_round == reporting_round_id {
+                self.update_oracle_stats(oracle, |stats| stats.rounds_eligible += 1);
+                self.oracle_liveness_round(oracle).set(&reporting_round_id);
+            } else {
+                self.oracle_liveness_round(oracle)
+                    .set(&reporting_round_id.saturating_sub(1));
+            }
+        }
+        oracles.len()
+    }
+
     #[endpoint(addFunds)]
     #[payable("*")]
     fn add_funds(
@@ -107,287 +865,2701 @@ pub trait Aggregator {
         #[payment] payment: Self::BigUint,
         #[payment_token] token: TokenIdentifier,
     ) -> SCResult<()> {
-        require!(token == self.token_id().get(), "Wrong token type");
-        self.recorded_funds()
+        self.require_not_paused()?;
+        require!(self.is_accepted_token(&token), "Wrong token type");
+        self.settle_all_pending_earnings();
+        let available = self.recorded_funds(&token).get().available;
+        let mut total_shares = self.total_deposit_shares(&token).get();
+        if available == Self::BigUint::zero() && total_shares > Self::BigUint::zero() {
+            // the funds were all spent, so the outstanding shares are worthless
+            self.clear_deposit_shares(&token);
+            total_shares = Self::BigUint::zero();
+        }
+        let (shares, total_shares) =
+            deposits::shares_for_deposit(&payment, &available, &total_shares);
+
+        self.recorded_funds(&token)
             .update(|recorded_funds| recorded_funds.available += &payment);
         let caller = &self.blockchain().get_caller();
-        let deposit = self.get_deposit(caller) + payment;
-        self.set_deposit(caller, &deposit);
+        let caller_shares = self.get_deposit_shares(&token, caller) + shares;
+        self.set_deposit_shares(&token, caller, &caller_shares);
+        self.total_deposit_shares(&token).set(&total_shares);
+        self.settle_owed_payments(&token);
         Ok(())
     }
 
-    fn get_deposit(&self, address: &Address) -> Self::BigUint {
-        self.deposits().get(address).unwrap_or_else(|| 0u32.into())
+    /// Pays the owed payments of the token from its available funds, in the order the
+    /// oracles first became owed, as far as the funds allow.
+    fn settle_owed_payments(&self, token_id: &TokenIdentifier) {
+        let mut owed_payments = self.owed_payments(token_id);
+        let mut available = self.recorded_funds(token_id).get().available;
+        let mut settled = Self::BigUint::zero();
+        let oracles: Vec<Address> = owed_payments.keys().collect();
+        for oracle in oracles {
+            if available == Self::BigUint::zero() {
+                break;
+            }
+            let owed = owed_payments.get(&oracle).unwrap_or_else(Self::BigUint::zero);
+            let paid = if owed <= available {
+                owed_payments.remove(&oracle);
+                owed
+            } else {
+                owed_payments.insert(oracle.clone(), owed - available.clone());
+                available.clone()
+            };
+            available -= &paid;
+            settled += &paid;
+            self.oracle_withdrawable(&oracle, token_id)
+                .update(|withdrawable| *withdrawable += &paid);
+        }
+        self.recorded_funds(token_id).update(|recorded_funds| {
+            recorded_funds.available -= &settled;
+            recorded_funds.allocated += &settled;
+        });
     }
 
-    fn set_deposit(&self, address: &Address, amount: &Self::BigUint) {
-        if amount == &Self::BigUint::zero() {
-            self.deposits().remove(address);
-        } else {
-            self.deposits().insert(address.clone(), amount.clone());
+    fn add_owed_payment(
+        &self,
+        oracle: &Address,
+        token_id: &TokenIdentifier,
+        amount: Self::BigUint,
+    ) {
+        if amount == Self::BigUint::zero() {
+            return;
         }
+        let mut owed_payments = self.owed_payments(token_id);
+        let owed = owed_payments.get(oracle).unwrap_or_else(Self::BigUint::zero) + amount;
+        owed_payments.insert(oracle.clone(), owed);
     }
 
-    fn validate_submission_limits(&self, submission_values: &Vec<Self::BigUint>) -> SCResult<()> {
-        for value in submission_values.iter() {
-            require!(
-                value >= &self.min_submission_value().get(),
-                "value below min_submission_value"
-            );
-            require!(
-                value <= &self.max_submission_value().get(),
-                "value above max_submission_value"
-            );
+    /// Whitelists a token for deposits, so that it can later become the payment token.
+    #[endpoint(addAcceptedToken)]
+    fn add_accepted_token(&self, token_id: TokenIdentifier) -> SCResult<()> {
+        only_governance!(self, b"addAcceptedToken", token_id);
+        if self.recorded_funds(&token_id).is_empty() {
+            self.recorded_funds(&token_id).set(&Funds {
+                available: Self::BigUint::zero(),
+                allocated: Self::BigUint::zero(),
+            });
         }
+        self.accepted_tokens().insert(token_id);
         Ok(())
     }
 
-    #[endpoint(submit)]
-    fn submit(
-        &self,
-        round_id: u64,
-        #[var_args] submission_values: VarArgs<Self::BigUint>,
-    ) -> SCResult<()> {
+    /// Stops accepting deposits in a token. Funds already deposited or paid in it
+    /// remain withdrawable.
+    #[endpoint(removeAcceptedToken)]
+    fn remove_accepted_token(&self, token_id: TokenIdentifier) -> SCResult<()> {
+        only_governance!(self, b"removeAcceptedToken", token_id);
         require!(
-            submission_values.len() == self.values_count().get(),
-            "incorrect number of values in submission"
+            token_id != self.token_id().get(),
+            "cannot remove the payment token"
         );
-        self.validate_oracle_round(&self.blockchain().get_caller(), &round_id)?;
-        let values = submission_values.into_vec();
-        self.validate_submission_limits(&values)?;
-        self.oracle_initialize_new_round(round_id)?;
-        self.record_submission(Submission { values }, round_id)?;
-        self.update_round_answer(round_id)?;
-        self.pay_oracle(round_id)?;
-        self.delete_round_details(round_id);
+        self.accepted_tokens().remove(&token_id);
         Ok(())
     }
 
-    #[endpoint(changeOracles)]
-    fn change_oracles(
+    /// Switches the token in which oracles are paid, starting with the next round.
+    /// Rounds already started are still paid in the token they started with.
+    #[endpoint(setPaymentToken)]
+    fn set_payment_token(
         &self,
-        removed: Vec<Address>,
-        added: Vec<Address>,
-        added_admins: Vec<Address>,
-        min_submissions: u64,
-        max_submissions: u64,
-        restart_delay: u64,
+        token_id: TokenIdentifier,
+        payment_amount: Self::BigUint,
     ) -> SCResult<()> {
-        only_owner!(self, "Only owner may call this function!");
+        only_governance!(self, b"setPaymentToken", token_id, payment_amount);
+        self.require_not_timelocked()?;
+        self.set_payment_token_internal(token_id, payment_amount)
+    }
 
-        for oracle in removed.iter() {
-            self.oracles().remove(oracle);
+    fn set_payment_token_internal(
+        &self,
+        token_id: TokenIdentifier,
+        payment_amount: Self::BigUint,
+    ) -> SCResult<()> {
+        require!(self.is_accepted_token(&token_id), "token not accepted");
+        let previous_token_id = self.token_id().get();
+        if previous_token_id != token_id {
+            self.accepted_tokens().insert(previous_token_id);
+            self.accepted_tokens().remove(&token_id);
+            self.token_id().set(&token_id);
         }
+        self.update_future_rounds_internal(
+            payment_amount,
+            self.min_submission_count().get(),
+            self.max_submission_count().get(),
+            self.restart_delay().get(),
+            self.timeout().get(),
+        )
+    }
+
+    /// Replaces the payment token, e.g. after a token swap or bridge migration: the
+    /// depositors get back their part of the available funds and the oracles their
+    /// withdrawable payments in the old token, which is no longer accepted. The open
+    /// rounds are closed first, so that no round pays in the old token afterwards, and
+    /// the funds in the new token must cover the required reserve.
+    #[endpoint(migratePaymentToken)]
+    fn migrate_payment_token(&self, new_token: TokenIdentifier) -> SCResult<()> {
+        only_governance!(self, b"migratePaymentToken", new_token);
+        self.require_not_timelocked()?;
+        self.migrate_payment_token_internal(new_token)
+    }
 
+    fn migrate_payment_token_internal(&self, new_token: TokenIdentifier) -> SCResult<()> {
+        let old_token = self.token_id().get();
+        require!(new_token != old_token, "token already used for payments");
+        require!(self.is_accepted_token(&new_token), "token not accepted");
         require!(
-            added.len() == added_admins.len(),
-            "need same oracle and admin count"
+            self.get_recorded_funds(&new_token).available
+                >= self.required_reserve(&self.payment_amount().get()),
+            "insufficient reserve funds in new token"
         );
 
-        for (added_oracle, added_admin) in added.iter().zip(added_admins.iter()) {
-            self.add_oracle(added_oracle, added_admin)?;
+        let open_rounds: Vec<u64> = self.details().keys().collect();
+        for round_id in open_rounds {
+            self.force_close_round(round_id)?;
+        }
+        self.settle_all_pending_earnings();
+        self.refund_depositors(&old_token);
+        let oracles: Vec<Address> = self.oracles().keys().collect();
+        for oracle in oracles {
+            let withdrawable = self.oracle_withdrawable(&oracle, &old_token).get();
+            if withdrawable == Self::BigUint::zero() {
+                continue;
+            }
+            let recipient = self.get_payout_address(oracle.clone())?;
+            self.recorded_funds(&old_token)
+                .update(|recorded_funds| recorded_funds.allocated -= &withdrawable);
+            self.oracle_withdrawable(&oracle, &old_token).clear();
+            self.send_funds(&old_token, &recipient, &withdrawable, b"");
         }
 
-        self.update_future_rounds_internal(
-            self.payment_amount().get(),
-            min_submissions,
-            max_submissions,
-            restart_delay,
-            self.timeout().get(),
-        )?;
+        self.accepted_tokens().remove(&new_token);
+        self.token_id().set(&new_token);
+        self.payment_token_migrated_event(&old_token, &new_token);
         Ok(())
     }
 
-    #[endpoint(updateFutureRounds)]
-    fn update_future_rounds(
+    /// Replaces any pending proposal.
+    #[endpoint(proposeSetPaymentToken)]
+    fn propose_set_payment_token(
         &self,
+        token_id: TokenIdentifier,
         payment_amount: Self::BigUint,
-        min_submissions: u64,
-        max_submissions: u64,
-        restart_delay: u64,
-        timeout: u64,
     ) -> SCResult<()> {
-        only_owner!(self, "Only owner may call this function!");
-        self.update_future_rounds_internal(
+        only_governance!(self, b"proposeSetPaymentToken", token_id, payment_amount);
+        let execute_after = self.timelock_execute_after();
+        self.payment_token_proposal().set(&PaymentTokenProposal {
+            token_id,
             payment_amount,
-            min_submissions,
-            max_submissions,
-            restart_delay,
-            timeout,
-        )
+            execute_after,
+        });
+        self.config_change_proposed_event(b"setPaymentToken", execute_after);
+        Ok(())
     }
 
-    fn update_future_rounds_internal(
-        &self,
-        payment_amount: Self::BigUint,
-        min_submissions: u64,
-        max_submissions: u64,
-        restart_delay: u64,
-        timeout: u64,
-    ) -> SCResult<()> {
-        let oracle_count = self.oracle_count();
+    /// The proposal is validated when executed, against the state at that time.
+    #[endpoint(executeSetPaymentToken)]
+    fn execute_set_payment_token(&self) -> SCResult<()> {
+        only_governance!(self, b"executeSetPaymentToken");
         require!(
-            max_submissions >= min_submissions,
-            "max must equal/exceed min"
+            !self.payment_token_proposal().is_empty(),
+            "no pending proposal"
         );
-        require!(max_submissions <= oracle_count, "max cannot exceed total");
+        let proposal = self.payment_token_proposal().get();
         require!(
-            oracle_count == 0 || restart_delay < oracle_count,
-            "delay cannot exceed total"
+            self.blockchain().get_block_timestamp() >= proposal.execute_after,
+            "proposal still timelocked"
+        );
+        self.payment_token_proposal().clear();
+        self.set_payment_token_internal(proposal.token_id, proposal.payment_amount)
+    }
+
+    #[endpoint(cancelSetPaymentToken)]
+    fn cancel_set_payment_token(&self) -> SCResult<()> {
+        only_governance!(self, b"cancelSetPaymentToken");
+        require!(
+            !self.payment_token_proposal().is_empty(),
+            "no pending proposal"
         );
+        self.payment_token_proposal().clear();
+        Ok(())
+    }
 
-        let recorded_funds = self.recorded_funds().get();
+    #[view(getPaymentTokenProposal)]
+    fn get_payment_token_proposal(&self) -> OptionalResult<PaymentTokenProposal<Self::BigUint>> {
+        if self.payment_token_proposal().is_empty() {
+            OptionalResult::None
+        } else {
+            OptionalResult::Some(self.payment_token_proposal().get())
+        }
+    }
+
+    /// Replaces any pending proposal.
+    #[endpoint(proposeMigratePaymentToken)]
+    fn propose_migrate_payment_token(&self, new_token: TokenIdentifier) -> SCResult<()> {
+        only_governance!(self, b"proposeMigratePaymentToken", new_token);
+        let execute_after = self.timelock_execute_after();
+        self.token_migration_proposal().set(&TokenMigrationProposal {
+            new_token,
+            execute_after,
+        });
+        self.config_change_proposed_event(b"migratePaymentToken", execute_after);
+        Ok(())
+    }
 
+    /// The proposal is validated when executed, against the state at that time.
+    #[endpoint(executeMigratePaymentToken)]
+    fn execute_migrate_payment_token(&self) -> SCResult<()> {
+        only_governance!(self, b"executeMigratePaymentToken");
         require!(
-            recorded_funds.available >= self.required_reserve(&payment_amount),
-            "insufficient funds for payment"
+            !self.token_migration_proposal().is_empty(),
+            "no pending proposal"
+        );
+        let proposal = self.token_migration_proposal().get();
+        require!(
+            self.blockchain().get_block_timestamp() >= proposal.execute_after,
+            "proposal still timelocked"
         );
+        self.token_migration_proposal().clear();
+        self.migrate_payment_token_internal(proposal.new_token)
+    }
 
-        if oracle_count > 0 {
-            require!(min_submissions > 0, "min must be greater than 0");
+    #[endpoint(cancelMigratePaymentToken)]
+    fn cancel_migrate_payment_token(&self) -> SCResult<()> {
+        only_governance!(self, b"cancelMigratePaymentToken");
+        require!(
+            !self.token_migration_proposal().is_empty(),
+            "no pending proposal"
+        );
+        self.token_migration_proposal().clear();
+        Ok(())
+    }
+
+    #[view(getTokenMigrationProposal)]
+    fn get_token_migration_proposal(&self) -> OptionalResult<TokenMigrationProposal> {
+        if self.token_migration_proposal().is_empty() {
+            OptionalResult::None
+        } else {
+            OptionalResult::Some(self.token_migration_proposal().get())
         }
-        self.payment_amount().set(&payment_amount);
-        self.min_submission_count().set(&min_submissions);
-        self.max_submission_count().set(&max_submissions);
-        self.restart_delay().set(&restart_delay);
-        self.timeout().set(&timeout);
+    }
+
+    /// Sends every depositor its part of the available funds of the token.
+    fn refund_depositors(&self, token_id: &TokenIdentifier) {
+        let available = self.get_recorded_funds(token_id).available;
+        let total_shares = self.total_deposit_shares(token_id).get();
+        let mut refunded = Self::BigUint::zero();
+        for (depositor, shares) in self.deposit_shares(token_id).iter() {
+            let amount = deposits::claim_for_shares(&shares, &available, &total_shares);
+            if amount > Self::BigUint::zero() {
+                self.send_funds(token_id, &depositor, &amount, b"");
+                refunded += &amount;
+            }
+        }
+        self.clear_deposit_shares(token_id);
+        self.recorded_funds(token_id)
+            .update(|recorded_funds| recorded_funds.available -= &refunded);
+    }
+
+    /// Sends ESDT tokens transferred to the contract by mistake to `to`. The payment
+    /// token, the other accepted tokens, the stake token and any former payment token
+    /// still holding funds belong to the feed and can never be rescued.
+    #[endpoint(rescueTokens)]
+    fn rescue_tokens(
+        &self,
+        token_id: TokenIdentifier,
+        amount: Self::BigUint,
+        to: Address,
+    ) -> SCResult<()> {
+        only_governance!(self, b"rescueTokens", token_id, amount, to);
+        require!(
+            token_id != self.token_id().get(),
+            "cannot rescue the payment token"
+        );
+        require!(!token_id.is_egld(), "cannot rescue EGLD");
+        require!(
+            !self.accepted_tokens().contains(&token_id),
+            "cannot rescue an accepted token"
+        );
+        require!(
+            self.stake_token_id().is_empty() || self.stake_token_id().get() != token_id,
+            "cannot rescue the stake token"
+        );
+        let recorded_funds = self.get_recorded_funds(&token_id);
+        require!(
+            recorded_funds.available == Self::BigUint::zero()
+                && recorded_funds.allocated == Self::BigUint::zero(),
+            "cannot rescue a token holding funds of the feed"
+        );
+        self.send().direct(&to, &token_id, &amount, b"rescued tokens");
         Ok(())
     }
 
-    #[view(allocatedFunds)]
-    fn allocated_funds(&self) -> Self::BigUint {
-        self.recorded_funds().get().allocated
+    #[view(getAcceptedTokens)]
+    fn get_accepted_tokens(&self) -> MultiResultVec<TokenIdentifier> {
+        let mut tokens = Vec::new();
+        tokens.push(self.token_id().get());
+        tokens.extend(self.accepted_tokens().iter());
+        tokens.into()
     }
 
-    #[view(availableFunds)]
-    fn available_funds(&self) -> Self::BigUint {
-        self.recorded_funds().get().available
+    fn is_accepted_token(&self, token_id: &TokenIdentifier) -> bool {
+        token_id == &self.token_id().get() || self.accepted_tokens().contains(token_id)
     }
 
-    #[view(oracleCount)]
-    fn oracle_count(&self) -> u64 {
-        self.oracles().len() as u64
+    fn token_or_payment_token(&self, token_id: OptionalArg<TokenIdentifier>) -> TokenIdentifier {
+        match token_id {
+            OptionalArg::Some(token_id) => token_id,
+            OptionalArg::None => self.token_id().get(),
+        }
     }
 
-    #[view(getRoundData)]
-    fn get_round_data(&self, round_id: u64) -> OptionalResult<Round<Self::BigUint>> {
-        self.rounds().get(&round_id).into()
+    fn get_recorded_funds(&self, token_id: &TokenIdentifier) -> Funds<Self::BigUint> {
+        if self.recorded_funds(token_id).is_empty() {
+            return Funds {
+                available: Self::BigUint::zero(),
+                allocated: Self::BigUint::zero(),
+            };
+        }
+        self.recorded_funds(token_id).get()
     }
 
-    #[view(latestRoundData)]
-    fn latest_round_data(&self) -> OptionalResult<Round<Self::BigUint>> {
-        self.get_round_data(self.latest_round_id().get())
+    /// Sends either EGLD or an ESDT token.
+    fn send_funds(
+        &self,
+        token_id: &TokenIdentifier,
+        to: &Address,
+        amount: &Self::BigUint,
+        data: &[u8],
+    ) {
+        if token_id.is_egld() {
+            self.send().direct_egld(to, amount, data);
+        } else {
+            self.send().direct(to, token_id, amount, data);
+        }
     }
 
-    #[view(withdrawablePayment)]
-    fn withdrawable_payment(&self, oracle: Address) -> SCResult<Self::BigUint> {
-        Ok(self.get_oracle_status_result(&oracle)?.withdrawable)
+    /// The part of the available funds claimable by a depositor.
+    fn get_deposit(&self, token_id: &TokenIdentifier, address: &Address) -> Self::BigUint {
+        deposits::claim_for_shares(
+            &self.get_deposit_shares(token_id, address),
+            &self.get_recorded_funds(token_id).available,
+            &self.total_deposit_shares(token_id).get(),
+        )
     }
 
-    #[endpoint(withdrawPayment)]
-    fn withdraw_payment(
+    fn get_deposit_shares(&self, token_id: &TokenIdentifier, address: &Address) -> Self::BigUint {
+        self.deposit_shares(token_id)
+            .get(address)
+            .unwrap_or_else(|| 0u32.into())
+    }
+
+    fn set_deposit_shares(
+        &self,
+        token_id: &TokenIdentifier,
+        address: &Address,
+        shares: &Self::BigUint,
+    ) {
+        if shares == &Self::BigUint::zero() {
+            self.deposit_shares(token_id).remove(address);
+        } else {
+            self.deposit_shares(token_id)
+                .insert(address.clone(), shares.clone());
+        }
+    }
+
+    fn clear_deposit_shares(&self, token_id: &TokenIdentifier) {
+        let holders: Vec<Address> = self.deposit_shares(token_id).keys().collect();
+        for holder in holders.iter() {
+            self.deposit_shares(token_id).remove(holder);
+        }
+        self.total_deposit_shares(token_id).clear();
+    }
+
+    /// In signed rounds (a non-zero `value_offset`) the lower bound is `-value_offset`
+    /// instead of `min_submission_value`.
+    fn validate_submission_limits(
+        &self,
+        submission: &Submission<Self::BigUint>,
+        value_offset: &Self::BigUint,
+    ) -> SCResult<()> {
+        let signed = value_offset > &Self::BigUint::zero();
+        for (index, value) in submission.values.iter().enumerate() {
+            if submission.is_negative(index) {
+                require!(signed, "negative values not accepted");
+                require!(value <= value_offset, "value below negative_value_bound");
+                continue;
+            }
+            require!(
+                signed || value >= &self.min_submission_value().get(),
+                "value below min_submission_value"
+            );
+            require!(
+                value <= &self.max_submission_value().get(),
+                "value above max_submission_value"
+            );
+        }
+        Ok(())
+    }
+
+    #[endpoint(submit)]
+    fn submit(
+        &self,
+        round_id: u64,
+        #[var_args] submission_values: VarArgs<Self::BigUint>,
+    ) -> SCResult<()> {
+        self.require_not_paused()?;
+        self.submit_internal(
+            &self.submitting_oracle(),
+            round_id,
+            Submission::from_values(submission_values.into_vec()),
+        )
+    }
+
+    /// Submits values which may be negative: `negative` flags the negative values,
+    /// which are only accepted while the negative value bound is set.
+    #[endpoint(submitSignedValues)]
+    fn submit_signed_values(
+        &self,
+        round_id: u64,
+        negative: Vec<bool>,
+        #[var_args] submission_values: VarArgs<Self::BigUint>,
+    ) -> SCResult<()> {
+        self.require_not_paused()?;
+        self.submit_internal(
+            &self.submitting_oracle(),
+            round_id,
+            Submission {
+                values: submission_values.into_vec(),
+                negative,
+            },
+        )
+    }
+
+    /// Submits values on behalf of an oracle, authorized by a signature of the oracle's
+    /// registered signing key (see `report::submission_message`) instead of the caller.
+    #[endpoint(submitSigned)]
+    fn submit_signed(
         &self,
         oracle: Address,
-        recipient: Address,
-        amount: Self::BigUint,
+        round_id: u64,
+        values: Vec<Self::BigUint>,
+        signature: BoxedBytes,
     ) -> SCResult<()> {
-        let mut oracle_status = self.get_oracle_status_result(&oracle)?;
+        self.require_not_paused()?;
         require!(
-            oracle_status.admin == self.blockchain().get_caller(),
-            "only callable by admin"
+            self.signing_keys().contains_key(&oracle),
+            "no signing key registered"
+        );
+        let message = report::submission_message(
+            &self.blockchain().get_sc_address(),
+            &oracle,
+            round_id,
+            &values,
+        );
+        require!(
+            self.crypto().verify_ed25519(
+                self.oracle_signing_key(&oracle).as_slice(),
+                &message,
+                signature.as_slice(),
+            ),
+            "invalid submission signature"
         );
+        self.submit_internal(&oracle, round_id, Submission::from_values(values))
+    }
+
+    fn submit_internal(
+        &self,
+        oracle: &Address,
+        round_id: u64,
+        submission: Submission<Self::BigUint>,
+    ) -> SCResult<()> {
+        self.validate_oracle_round(oracle, &round_id)?;
+        self.oracle_initialize_new_round(round_id, oracle)?;
+        let position = self.record_submission(submission, round_id, oracle)?;
+        self.update_round_answer(round_id)?;
+        self.pay_oracle(round_id, oracle, position)?;
+        self.delete_round_details(round_id);
+        Ok(())
+    }
 
+    /// Submits the observations of several oracles for a round in a single transaction.
+    /// Every observing oracle must sign the whole report (see `report::report_message`)
+    /// with its signing key, and the report must contain enough observations, from distinct
+    /// oracles, to answer the round.
+    #[endpoint(submitReport)]
+    fn submit_report(
+        &self,
+        round_id: u64,
+        #[var_args] observations: VarArgs<ReportObservation<Self::BigUint>>,
+    ) -> SCResult<()> {
+        self.require_not_paused()?;
+        let observations = observations.into_vec();
+        require!(!observations.is_empty(), "empty report");
+        require!(
+            observations.len() as u64 >= self.min_submission_count().get(),
+            "not enough observations in report"
+        );
         require!(
-            oracle_status.withdrawable >= amount,
-            "insufficient withdrawable funds"
+            observations.len() as u64 <= self.max_submission_count().get(),
+            "too many observations in report"
         );
 
-        self.recorded_funds()
-            .update(|recorded_funds| recorded_funds.allocated -= &amount);
-        oracle_status.withdrawable -= &amount;
-        self.oracles().insert(oracle, oracle_status);
+        let message =
+            report::report_message(&self.blockchain().get_sc_address(), round_id, &observations);
+        let mut observing_oracles: Vec<&Address> = Vec::with_capacity(observations.len());
+        for observation in observations.iter() {
+            require!(
+                !observing_oracles.contains(&&observation.oracle),
+                "duplicate oracle in report"
+            );
+            observing_oracles.push(&observation.oracle);
+            self.validate_oracle_round(&observation.oracle, &round_id)?;
+            require!(
+                self.crypto().verify_ed25519(
+                    self.oracle_signing_key(&observation.oracle).as_slice(),
+                    &message,
+                    observation.signature.as_slice(),
+                ),
+                "invalid report signature"
+            );
+        }
 
-        self.send()
-            .direct(&recipient, &self.token_id().get(), &amount, b"");
+        self.oracle_initialize_new_round(round_id, &observations[0].oracle)?;
+        let mut positions = Vec::with_capacity(observations.len());
+        for observation in observations.iter() {
+            let submission = Submission {
+                values: observation.values.clone(),
+                negative: observation.negative.clone(),
+            };
+            positions.push(self.record_submission(submission, round_id, &observation.oracle)?);
+        }
+        self.update_round_answer(round_id)?;
+        require!(
+            self.latest_round_id().get() == round_id,
+            "report did not answer the round"
+        );
+        for (observation, position) in observations.iter().zip(positions.into_iter()) {
+            self.pay_oracle(round_id, &observation.oracle, position)?;
+        }
+        self.delete_round_details(round_id);
         Ok(())
     }
 
-    #[view(withdrawableAddedFunds)]
-    fn withdrawable_added_funds(&self) -> Self::BigUint {
-        self.get_deposit(&self.blockchain().get_caller())
+    #[endpoint(changeOracles)]
+    fn change_oracles(
+        &self,
+        removed: Vec<Address>,
+        added: Vec<Address>,
+        added_admins: Vec<Address>,
+        min_submissions: u64,
+        max_submissions: u64,
+        restart_delay: u64,
+        #[var_args] added_weights: OptionalArg<Vec<u64>>,
+    ) -> SCResult<()> {
+        let added_weights = added_weights.into_option();
+        only_governance!(
+            self,
+            b"changeOracles",
+            removed,
+            added,
+            added_admins,
+            min_submissions,
+            max_submissions,
+            restart_delay,
+            added_weights,
+        );
+        self.require_not_timelocked()?;
+        self.change_oracles_internal(
+            removed,
+            added,
+            added_admins,
+            added_weights,
+            min_submissions,
+            max_submissions,
+            restart_delay,
+        )
     }
 
-    #[endpoint(withdrawFunds)]
-    fn withdraw_funds(&self, amount: Self::BigUint) -> SCResult<()> {
-        let recorded_funds = self.recorded_funds().get();
-        let caller = &self.blockchain().get_caller();
-        let deposit = self.get_deposit(caller);
-        require!(amount <= deposit, "Insufficient funds to withdraw");
+    fn change_oracles_internal(
+        &self,
+        removed: Vec<Address>,
+        added: Vec<Address>,
+        added_admins: Vec<Address>,
+        added_weights: Option<Vec<u64>>,
+        min_submissions: u64,
+        max_submissions: u64,
+        restart_delay: u64,
+    ) -> SCResult<()> {
+        for oracle in removed.iter() {
+            self.remove_oracle(oracle);
+        }
+
         require!(
-            recorded_funds.available - self.required_reserve(&self.payment_amount().get())
-                >= amount,
-            "insufficient reserve funds"
+            added.len() == added_admins.len(),
+            "need same oracle and admin count"
         );
-        self.recorded_funds()
-            .update(|recorded_funds| recorded_funds.available -= &amount);
-        let remaining = &deposit - &amount;
-        self.set_deposit(caller, &remaining);
-        self.send()
-            .direct(caller, &self.token_id().get(), &amount, b"withdraw");
+
+        for (added_oracle, added_admin) in added.iter().zip(added_admins.iter()) {
+            self.add_oracle(added_oracle, added_admin)?;
+        }
+
+        if let Some(weights) = added_weights {
+            require!(
+                added.len() == weights.len(),
+                "need same oracle and weight count"
+            );
+            for (added_oracle, weight) in added.iter().zip(weights.iter()) {
+                self.set_oracle_weight(added_oracle, *weight)?;
+            }
+        }
+
+        self.update_future_rounds_internal(
+            self.payment_amount().get(),
+            min_submissions,
+            max_submissions,
+            restart_delay,
+            self.timeout().get(),
+        )?;
         Ok(())
     }
 
-    #[view(getAdmin)]
-    fn get_admin(&self, oracle: Address) -> SCResult<Address> {
-        Ok(self.get_oracle_status_result(&oracle)?.admin)
+    /// Reconfigures the whole oracle set at once: the listed oracles are added or updated,
+    /// all the other oracles are removed.
+    #[endpoint(configureOracles)]
+    fn configure_oracles(
+        &self,
+        configs: Vec<OracleConfig<Self::BigUint>>,
+        min_submissions: u64,
+        max_submissions: u64,
+        restart_delay: u64,
+    ) -> SCResult<()> {
+        only_governance!(
+            self,
+            b"configureOracles",
+            configs,
+            min_submissions,
+            max_submissions,
+            restart_delay,
+        );
+        self.require_not_timelocked()?;
+        self.configure_oracles_internal(configs, min_submissions, max_submissions, restart_delay)
+    }
+
+    fn configure_oracles_internal(
+        &self,
+        configs: Vec<OracleConfig<Self::BigUint>>,
+        min_submissions: u64,
+        max_submissions: u64,
+        restart_delay: u64,
+    ) -> SCResult<()> {
+        for (index, config) in configs.iter().enumerate() {
+            require!(
+                configs[..index]
+                    .iter()
+                    .all(|previous| previous.address != config.address),
+                "duplicate oracle"
+            );
+        }
+
+        let removed: Vec<Address> = self
+            .oracles()
+            .keys()
+            .filter(|oracle| !configs.iter().any(|config| &config.address == oracle))
+            .collect();
+        for oracle in removed.iter() {
+            self.remove_oracle(oracle);
+        }
+
+        for config in configs.iter() {
+            if self.oracle_enabled(&config.address) {
+                require!(
+                    self.get_oracle_status_result(&config.address)?.admin == config.admin,
+                    "owner cannot overwrite admin"
+                );
+            } else {
+                self.add_oracle(&config.address, &config.admin)?;
+            }
+            self.set_oracle_weight(&config.address, config.weight)?;
+            match &config.payment_override {
+                Some(payment) => {
+                    self.oracle_payment_overrides()
+                        .insert(config.address.clone(), payment.clone());
+                }
+                None => {
+                    self.oracle_payment_overrides().remove(&config.address);
+                }
+            }
+        }
+
+        self.update_future_rounds_internal(
+            self.payment_amount().get(),
+            min_submissions,
+            max_submissions,
+            restart_delay,
+            self.timeout().get(),
+        )
+    }
+
+    /// Replaces any pending proposal.
+    #[endpoint(proposeConfigureOracles)]
+    fn propose_configure_oracles(
+        &self,
+        configs: Vec<OracleConfig<Self::BigUint>>,
+        min_submissions: u64,
+        max_submissions: u64,
+        restart_delay: u64,
+    ) -> SCResult<()> {
+        only_governance!(
+            self,
+            b"proposeConfigureOracles",
+            configs,
+            min_submissions,
+            max_submissions,
+            restart_delay,
+        );
+        let execute_after = self.timelock_execute_after();
+        self.oracle_configs_proposal().set(&OracleConfigsProposal {
+            configs,
+            min_submissions,
+            max_submissions,
+            restart_delay,
+            execute_after,
+        });
+        self.config_change_proposed_event(b"configureOracles", execute_after);
+        Ok(())
+    }
+
+    /// The proposal is validated when executed, against the state at that time.
+    #[endpoint(executeConfigureOracles)]
+    fn execute_configure_oracles(&self) -> SCResult<()> {
+        only_governance!(self, b"executeConfigureOracles");
+        require!(
+            !self.oracle_configs_proposal().is_empty(),
+            "no pending proposal"
+        );
+        let proposal = self.oracle_configs_proposal().get();
+        require!(
+            self.blockchain().get_block_timestamp() >= proposal.execute_after,
+            "proposal still timelocked"
+        );
+        self.oracle_configs_proposal().clear();
+        self.configure_oracles_internal(
+            proposal.configs,
+            proposal.min_submissions,
+            proposal.max_submissions,
+            proposal.restart_delay,
+        )
+    }
+
+    #[endpoint(cancelConfigureOracles)]
+    fn cancel_configure_oracles(&self) -> SCResult<()> {
+        only_governance!(self, b"cancelConfigureOracles");
+        require!(
+            !self.oracle_configs_proposal().is_empty(),
+            "no pending proposal"
+        );
+        self.oracle_configs_proposal().clear();
+        Ok(())
+    }
+
+    #[view(getOracleConfigsProposal)]
+    fn get_oracle_configs_proposal(
+        &self,
+    ) -> OptionalResult<OracleConfigsProposal<Self::BigUint>> {
+        if self.oracle_configs_proposal().is_empty() {
+            OptionalResult::None
+        } else {
+            OptionalResult::Some(self.oracle_configs_proposal().get())
+        }
+    }
+
+    #[view(getOraclePaymentOverride)]
+    fn get_oracle_payment_override(&self, oracle: Address) -> OptionalResult<Self::BigUint> {
+        self.oracle_payment_overrides().get(&oracle).into()
+    }
+
+    /// The payment of an oracle for a round paying `round_payment`.
+    fn oracle_payment(&self, oracle: &Address, round_payment: Self::BigUint) -> Self::BigUint {
+        self.oracle_payment_overrides()
+            .get(oracle)
+            .unwrap_or(round_payment)
+    }
+
+    fn remove_oracle(&self, oracle: &Address) {
+        self.settle_pending_earnings(oracle);
+        self.settle_oracle_liveness(oracle);
+        self.oracle_weights().remove(oracle);
+        self.oracle_payment_overrides().remove(oracle);
+        if self.oracles().remove(oracle).is_some() {
+            self.oracle_permissions_updated_event(oracle, false);
+        }
+    }
+
+    fn set_oracle_weight(&self, oracle: &Address, weight: u64) -> SCResult<()> {
+        require!(weight > 0, "weight must be greater than 0");
+        if weight == DEFAULT_ORACLE_WEIGHT {
+            self.oracle_weights().remove(oracle);
+        } else {
+            self.oracle_weights().insert(oracle.clone(), weight);
+        }
+        Ok(())
+    }
+
+    #[view(getOracleWeight)]
+    fn get_oracle_weight(&self, oracle: Address) -> u64 {
+        self.oracle_weight(&oracle)
+    }
+
+    fn oracle_weight(&self, oracle: &Address) -> u64 {
+        self.oracle_weights()
+            .get(oracle)
+            .unwrap_or(DEFAULT_ORACLE_WEIGHT)
+    }
+
+    #[endpoint(setOracleEndingRound)]
+    fn set_oracle_ending_round(&self, oracle: Address, round_id: u64) -> SCResult<()> {
+        only_governance!(self, b"setOracleEndingRound", oracle, round_id);
+        let mut oracle_status = self.get_oracle_status_result(&oracle)?;
+        require!(
+            round_id >= self.reporting_round_id().get(),
+            "ending round already passed"
+        );
+        require!(
+            round_id >= oracle_status.starting_round,
+            "ending round before starting round"
+        );
+        oracle_status.ending_round = round_id;
+        self.oracles().insert(oracle.clone(), oracle_status);
+        self.oracle_permissions_updated_event(&oracle, false);
+        Ok(())
+    }
+
+    /// Lets a trusted oracle start rounds more (or less) frequently than the restart
+    /// delay of the feed; without `restart_delay` the oracle uses the feed's delay again.
+    #[endpoint(setOracleRestartDelay)]
+    fn set_oracle_restart_delay(
+        &self,
+        oracle: Address,
+        #[var_args] restart_delay: OptionalArg<u64>,
+    ) -> SCResult<()> {
+        let restart_delay = restart_delay.into_option();
+        only_governance!(self, b"setOracleRestartDelay", oracle, restart_delay);
+        let mut oracle_status = self.get_oracle_status_result(&oracle)?;
+        oracle_status.restart_delay = restart_delay;
+        self.oracles().insert(oracle, oracle_status);
+        Ok(())
+    }
+
+    #[view(getOracleRestartDelay)]
+    fn get_oracle_restart_delay(&self, oracle: Address) -> SCResult<u64> {
+        let oracle_status = self.get_oracle_status_result(&oracle)?;
+        Ok(self.oracle_restart_delay(&oracle_status))
+    }
+
+    fn oracle_restart_delay(&self, oracle_status: &OracleStatus<Self::BigUint>) -> u64 {
+        oracle_status
+            .restart_delay
+            .unwrap_or_else(|| self.restart_delay().get())
+    }
+
+    /// Rotates the oracles: each epoch, `committee_size` of the enabled oracles are selected
+    /// with `selectCommittee`, and only they can submit during the epoch.
+    /// 0 disables the rotation.
+    #[endpoint(setCommitteeSize)]
+    fn set_committee_size(&self, committee_size: u64) -> SCResult<()> {
+        only_governance!(self, b"setCommitteeSize", committee_size);
+        require!(
+            committee_size == 0 || committee_size >= self.max_submission_count().get(),
+            "committee smaller than max submissions"
+        );
+        self.committee_size().set(&committee_size);
+        self.committee_epoch().clear();
+        Ok(())
+    }
+
+    /// The committee of the current epoch, if it was already selected.
+    #[view(getCommittee)]
+    fn get_committee(&self) -> MultiResultVec<Address> {
+        if self.committee_epoch().is_empty()
+            || self.committee_epoch().get() != self.blockchain().get_block_epoch()
+        {
+            return MultiResultVec::new();
+        }
+        self.committee().iter().collect()
+    }
+
+    /// Selects the committee of the current epoch using the random seed of the previous
+    /// block. Callable by anyone, but only once per epoch, so the seed cannot be retried
+    /// until it selects a favourable committee. Nobody can submit until it is selected.
+    #[endpoint(selectCommittee)]
+    fn select_committee(&self) -> SCResult<()> {
+        let committee_size = self.committee_size().get();
+        require!(committee_size > 0, "oracle rotation disabled");
+        let epoch = self.blockchain().get_block_epoch();
+        require!(
+            self.committee_epoch().is_empty() || self.committee_epoch().get() != epoch,
+            "committee already selected for this epoch"
+        );
+        let pool: Vec<Address> = self
+            .oracles()
+            .keys()
+            .filter(|oracle| self.oracle_enabled(oracle))
+            .collect();
+        let seed = rotation::seed_from_bytes(&self.blockchain().get_prev_block_random_seed()[..]);
+        let mut committee = self.committee();
+        committee.clear();
+        for member in rotation::select_committee(&pool, committee_size as usize, seed ^ epoch) {
+            committee.insert(member);
+        }
+        self.committee_epoch().set(&epoch);
+        self.committee_selected_event(epoch, committee_size);
+        Ok(())
+    }
+
+    fn require_committee_member(&self, oracle: &Address) -> SCResult<()> {
+        if self.committee_size().get() == 0 {
+            return Ok(());
+        }
+        let epoch = self.blockchain().get_block_epoch();
+        require!(
+            !self.committee_epoch().is_empty() && self.committee_epoch().get() == epoch,
+            "committee not selected for this epoch"
+        );
+        require!(
+            self.committee().contains(oracle),
+            "oracle not in the committee of this epoch"
+        );
+        Ok(())
+    }
+
+    /// Rate-limits the submissions of every oracle to one per `interval` seconds;
+    /// 0 disables the limit.
+    #[endpoint(setMinSubmissionInterval)]
+    fn set_min_submission_interval(&self, interval: u64) -> SCResult<()> {
+        only_governance!(self, b"setMinSubmissionInterval", interval);
+        self.min_submission_interval().set(&interval);
+        Ok(())
+    }
+
+    #[endpoint(updateFutureRounds)]
+    fn update_future_rounds(
+        &self,
+        payment_amount: Self::BigUint,
+        min_submissions: u64,
+        max_submissions: u64,
+        restart_delay: u64,
+        timeout: u64,
+    ) -> SCResult<()> {
+        only_governance!(
+            self,
+            b"updateFutureRounds",
+            payment_amount,
+            min_submissions,
+            max_submissions,
+            restart_delay,
+            timeout,
+        );
+        self.require_not_timelocked()?;
+        self.update_future_rounds_internal(
+            payment_amount,
+            min_submissions,
+            max_submissions,
+            restart_delay,
+            timeout,
+        )
+    }
+
+    /// The timelock can only be increased, so that a compromised owner cannot skip it.
+    #[endpoint(setConfigTimelock)]
+    fn set_config_timelock(&self, delay: u64) -> SCResult<()> {
+        only_governance!(self, b"setConfigTimelock", delay);
+        require!(
+            delay >= self.config_timelock().get(),
+            "timelock can only be increased"
+        );
+        self.config_timelock().set(&delay);
+        Ok(())
+    }
+
+    fn require_not_timelocked(&self) -> SCResult<()> {
+        require!(
+            self.config_timelock().get() == 0,
+            "configuration changes are timelocked"
+        );
+        Ok(())
+    }
+
+    fn timelock_execute_after(&self) -> u64 {
+        self.blockchain().get_block_timestamp() + self.config_timelock().get()
+    }
+
+    /// Replaces any pending proposal.
+    #[endpoint(proposeUpdateFutureRounds)]
+    fn propose_update_future_rounds(
+        &self,
+        payment_amount: Self::BigUint,
+        min_submissions: u64,
+        max_submissions: u64,
+        restart_delay: u64,
+        timeout: u64,
+    ) -> SCResult<()> {
+        only_governance!(
+            self,
+            b"proposeUpdateFutureRounds",
+            payment_amount,
+            min_submissions,
+            max_submissions,
+            restart_delay,
+            timeout,
+        );
+        let execute_after = self.timelock_execute_after();
+        self.future_rounds_proposal().set(&FutureRoundsProposal {
+            payment_amount,
+            min_submissions,
+            max_submissions,
+            restart_delay,
+            timeout,
+            execute_after,
+        });
+        self.config_change_proposed_event(b"updateFutureRounds", execute_after);
+        Ok(())
+    }
+
+    /// The proposal is validated when executed, against the state at that time.
+    #[endpoint(executeUpdateFutureRounds)]
+    fn execute_update_future_rounds(&self) -> SCResult<()> {
+        only_governance!(self, b"executeUpdateFutureRounds");
+        require!(
+            !self.future_rounds_proposal().is_empty(),
+            "no pending proposal"
+        );
+        let proposal = self.future_rounds_proposal().get();
+        require!(
+            self.blockchain().get_block_timestamp() >= proposal.execute_after,
+            "proposal still timelocked"
+        );
+        self.future_rounds_proposal().clear();
+        self.update_future_rounds_internal(
+            proposal.payment_amount,
+            proposal.min_submissions,
+            proposal.max_submissions,
+            proposal.restart_delay,
+            proposal.timeout,
+        )
+    }
+
+    #[endpoint(cancelUpdateFutureRounds)]
+    fn cancel_update_future_rounds(&self) -> SCResult<()> {
+        only_governance!(self, b"cancelUpdateFutureRounds");
+        require!(
+            !self.future_rounds_proposal().is_empty(),
+            "no pending proposal"
+        );
+        self.future_rounds_proposal().clear();
+        Ok(())
+    }
+
+    #[view(getFutureRoundsProposal)]
+    fn get_future_rounds_proposal(&self) -> OptionalResult<FutureRoundsProposal<Self::BigUint>> {
+        if self.future_rounds_proposal().is_empty() {
+            OptionalResult::None
+        } else {
+            OptionalResult::Some(self.future_rounds_proposal().get())
+        }
+    }
+
+    /// Replaces any pending proposal.
+    #[endpoint(proposeChangeOracles)]
+    fn propose_change_oracles(
+        &self,
+        removed: Vec<Address>,
+        added: Vec<Address>,
+        added_admins: Vec<Address>,
+        min_submissions: u64,
+        max_submissions: u64,
+        restart_delay: u64,
+        #[var_args] added_weights: OptionalArg<Vec<u64>>,
+    ) -> SCResult<()> {
+        let added_weights = added_weights.into_option();
+        only_governance!(
+            self,
+            b"proposeChangeOracles",
+            removed,
+            added,
+            added_admins,
+            min_submissions,
+            max_submissions,
+            restart_delay,
+            added_weights,
+        );
+        require!(
+            added.len() == added_admins.len(),
+            "need same oracle and admin count"
+        );
+        let execute_after = self.timelock_execute_after();
+        self.oracle_change_proposal().set(&OracleChangeProposal {
+            removed,
+            added,
+            added_admins,
+            added_weights,
+            min_submissions,
+            max_submissions,
+            restart_delay,
+            execute_after,
+        });
+        self.config_change_proposed_event(b"changeOracles", execute_after);
+        Ok(())
+    }
+
+    /// The proposal is validated when executed, against the state at that time.
+    #[endpoint(executeChangeOracles)]
+    fn execute_change_oracles(&self) -> SCResult<()> {
+        only_governance!(self, b"executeChangeOracles");
+        require!(
+            !self.oracle_change_proposal().is_empty(),
+            "no pending proposal"
+        );
+        let proposal = self.oracle_change_proposal().get();
+        require!(
+            self.blockchain().get_block_timestamp() >= proposal.execute_after,
+            "proposal still timelocked"
+        );
+        self.oracle_change_proposal().clear();
+        self.change_oracles_internal(
+            proposal.removed,
+            proposal.added,
+            proposal.added_admins,
+            proposal.added_weights,
+            proposal.min_submissions,
+            proposal.max_submissions,
+            proposal.restart_delay,
+        )
+    }
+
+    #[endpoint(cancelChangeOracles)]
+    fn cancel_change_oracles(&self) -> SCResult<()> {
+        only_governance!(self, b"cancelChangeOracles");
+        require!(
+            !self.oracle_change_proposal().is_empty(),
+            "no pending proposal"
+        );
+        self.oracle_change_proposal().clear();
+        Ok(())
+    }
+
+    #[view(getOracleChangeProposal)]
+    fn get_oracle_change_proposal(&self) -> OptionalResult<OracleChangeProposal> {
+        if self.oracle_change_proposal().is_empty() {
+            OptionalResult::None
+        } else {
+            OptionalResult::Some(self.oracle_change_proposal().get())
+        }
+    }
+
+    fn update_future_rounds_internal(
+        &self,
+        payment_amount: Self::BigUint,
+        min_submissions: u64,
+        max_submissions: u64,
+        restart_delay: u64,
+        timeout: u64,
+    ) -> SCResult<()> {
+        let oracle_count = self.oracle_count();
+        require!(
+            max_submissions >= min_submissions,
+            "max must equal/exceed min"
+        );
+        require!(max_submissions <= oracle_count, "max cannot exceed total");
+        require!(
+            oracle_count == 0 || restart_delay < oracle_count,
+            "delay cannot exceed total"
+        );
+
+        let recorded_funds = self.recorded_funds(&self.token_id().get()).get();
+
+        require!(
+            recorded_funds.available >= self.required_reserve(&payment_amount),
+            "insufficient funds for payment"
+        );
+
+        if oracle_count > 0 {
+            require!(min_submissions > 0, "min must be greater than 0");
+        }
+        self.payment_amount().set(&payment_amount);
+        self.min_submission_count().set(&min_submissions);
+        self.max_submission_count().set(&max_submissions);
+        self.restart_delay().set(&restart_delay);
+        self.timeout().set(&timeout);
+        self.round_details_updated_event(
+            min_submissions,
+            max_submissions,
+            restart_delay,
+            timeout,
+            &payment_amount,
+        );
+        Ok(())
+    }
+
+    #[view(allocatedFunds)]
+    fn allocated_funds(
+        &self,
+        #[var_args] token_id: OptionalArg<TokenIdentifier>,
+    ) -> Self::BigUint {
+        self.get_recorded_funds(&self.token_or_payment_token(token_id))
+            .allocated
+    }
+
+    #[view(availableFunds)]
+    fn available_funds(
+        &self,
+        #[var_args] token_id: OptionalArg<TokenIdentifier>,
+    ) -> Self::BigUint {
+        self.get_recorded_funds(&self.token_or_payment_token(token_id))
+            .available
+    }
+
+    /// Oracles given an ending round are no longer counted.
+    #[view(oracleCount)]
+    fn oracle_count(&self) -> u64 {
+        self.oracles()
+            .values()
+            .filter(|oracle_status| oracle_status.ending_round == ROUND_MAX)
+            .count() as u64
+    }
+
+    #[view(getRoundData)]
+    fn get_round_data(&self, round_id: u64) -> SCResult<OptionalResult<Round<Self::BigUint>>> {
+        self.require_read_access()?;
+        Ok(self.rounds().get(&round_id).into())
+    }
+
+    #[view(getRoundDataRange)]
+    fn get_round_data_range(
+        &self,
+        from_round: u64,
+        to_round: u64,
+    ) -> SCResult<MultiResultVec<Round<Self::BigUint>>> {
+        self.require_read_access()?;
+        require!(from_round <= to_round, "invalid round range");
+        require!(
+            to_round - from_round < MAX_ROUND_DATA_RANGE,
+            "round range too large"
+        );
+        Ok((from_round..=to_round)
+            .filter_map(|round_id| self.rounds().get(&round_id))
+            .collect())
+    }
+
+    /// Only available while the round is open, since the submissions are deleted once
+    /// the round is complete. Returns the magnitude of the quantile and whether it is
+    /// negative.
+    #[view(getRoundQuantile)]
+    fn get_round_quantile(
+        &self,
+        round_id: u64,
+        value_index: usize,
+        quantile_bp: u32,
+    ) -> SCResult<MultiArg2<Self::BigUint, bool>> {
+        self.require_read_access()?;
+        let details = self.get_round_details(&round_id)?;
+        require!(value_index < details.values_count, "invalid value index");
+        let values = self
+            .sorted_submission_values(round_id, value_index)
+            .get()
+            .into_iter()
+            .map(|(value, _)| value)
+            .collect();
+        match median::calculate_quantile(values, quantile_bp)? {
+            Some(shifted) => Ok(MultiArg2::from(median::unshift_signed(
+                &shifted,
+                &details.value_offset,
+            ))),
+            None => sc_error!("no submissions for given round id"),
+        }
+    }
+
+    /// The oracle, values, sign flags and timestamp of each submission of the round, in
+    /// the order they were received.
+    #[view(getRoundSubmissions)]
+    fn get_round_submissions(
+        &self,
+        round_id: u64,
+    ) -> SCResult<MultiResultVec<MultiArg4<Address, Vec<Self::BigUint>, Vec<bool>, u64>>> {
+        self.require_read_access()?;
+        Ok(self
+            .round_submissions(round_id)
+            .load_as_vec()
+            .into_iter()
+            .map(|round_submission| {
+                MultiArg4::from((
+                    round_submission.oracle,
+                    round_submission.submission.values,
+                    round_submission.submission.negative,
+                    round_submission.timestamp,
+                ))
+            })
+            .collect())
+    }
+
+    /// The oracle, token and amount of each payment held until the round is answered.
+    #[view(getEscrowedPayments)]
+    fn get_escrowed_payments(
+        &self,
+        round_id: u64,
+    ) -> MultiResultVec<MultiArg3<Address, TokenIdentifier, Self::BigUint>> {
+        self.escrowed_payments(round_id)
+            .load_as_vec()
+            .into_iter()
+            .map(|payment| MultiArg3::from((payment.oracle, payment.token_id, payment.amount)))
+            .collect()
+    }
+
+    #[view(latestRoundData)]
+    fn latest_round_data(&self) -> SCResult<OptionalResult<Round<Self::BigUint>>> {
+        self.get_round_data(self.latest_round_id().get())
+    }
+
+    /// Whether the answer of the round was carried over from a previous round, because
+    /// the round timed out or was closed without an answer of its own.
+    #[view(isAnswerCarriedOver)]
+    fn is_answer_carried_over(&self, round_id: u64) -> SCResult<bool> {
+        let round = self.get_round(&round_id)?;
+        Ok(round.answer.is_some() && !round.fresh)
+    }
+
+    #[view(getAnswer)]
+    fn get_answer(&self, round_id: u64) -> SCResult<OptionalResult<Submission<Self::BigUint>>> {
+        self.require_read_access()?;
+        Ok(self
+            .rounds()
+            .get(&round_id)
+            .and_then(|round| round.answer)
+            .into())
+    }
+
+    #[view(getTimestamp)]
+    fn get_timestamp(&self, round_id: u64) -> u64 {
+        self.rounds()
+            .get(&round_id)
+            .map_or_else(|| 0, |round| round.updated_at)
+    }
+
+    /// The first value of the latest answer; its magnitude, if the answer may be negative.
+    #[view(latestAnswer)]
+    fn latest_answer(&self) -> SCResult<OptionalResult<Self::BigUint>> {
+        Ok(self
+            .get_answer(self.latest_round_id().get())?
+            .into_option()
+            .and_then(|answer| answer.values.into_iter().next())
+            .into())
+    }
+
+    /// Whether the first value of the latest answer is negative, for the consumers of
+    /// `latestAnswer` reading feeds which accept negative values.
+    #[view(isLatestAnswerNegative)]
+    fn is_latest_answer_negative(&self) -> SCResult<OptionalResult<bool>> {
+        Ok(self
+            .get_answer(self.latest_round_id().get())?
+            .into_option()
+            .map(|answer| answer.is_negative(0))
+            .into())
+    }
+
+    /// The first value of the latest answer, converted from its decimals to
+    /// `target_decimals`, and whether it is negative. Lowering the precision rounds the
+    /// magnitude down.
+    #[view(latestAnswerScaled)]
+    fn latest_answer_scaled(
+        &self,
+        target_decimals: u8,
+    ) -> SCResult<OptionalResult<MultiArg2<Self::BigUint, bool>>> {
+        self.require_read_access()?;
+        Ok(self
+            .rounds()
+            .get(&self.latest_round_id().get())
+            .and_then(|round| {
+                let decimals = self
+                    .value_decimals()
+                    .get()
+                    .first()
+                    .cloned()
+                    .unwrap_or(round.decimals);
+                round.answer.and_then(|answer| {
+                    let negative = answer.is_negative(0);
+                    answer.values.into_iter().next().map(|value| {
+                        let scaled = scaling::rescale(&value, decimals, target_decimals);
+                        MultiArg2::from((scaled, negative))
+                    })
+                })
+            })
+            .into())
+    }
+
+    /// The time-weighted average of the first value of the answers over the last
+    /// `window_seconds` seconds, each answer weighted by the time it was the latest one.
+    /// The answers of the rounds which were already pruned are left out.
+    #[view(getTwap)]
+    fn get_twap(&self, window_seconds: u64) -> SCResult<OptionalResult<Self::BigUint>> {
+        self.require_read_access()?;
+        let now = self.blockchain().get_block_timestamp();
+        let window_start = now.saturating_sub(window_seconds);
+        let oldest_round_id = self.oldest_round_id().get();
+        let mut observations = Vec::new();
+        let mut round_id = self.latest_round_id().get();
+        while round_id > 0 && round_id >= oldest_round_id {
+            if let Some(round) = self.rounds().get(&round_id) {
+                if round.answered_in_round == round_id {
+                    if let Some(answer) = round.answer {
+                        require!(!answer.is_negative(0), "negative answers have no TWAP");
+                        if let Some(value) = answer.values.into_iter().next() {
+                            observations.push((round.updated_at, value));
+                        }
+                    }
+                    if round.updated_at <= window_start {
+                        break;
+                    }
+                }
+            }
+            round_id -= 1;
+        }
+        Ok(twap::time_weighted_average(&observations, window_start, now).into())
+    }
+
+    /// Mirrors a read access controller: while the check is enabled, contracts need to
+    /// be whitelisted to read the answers, while off-chain queries and reads by user
+    /// accounts stay open.
+    fn require_read_access(&self) -> SCResult<()> {
+        if !self.read_access_check_enabled().get() {
+            return Ok(());
+        }
+        let caller = self.blockchain().get_caller();
+        require!(
+            !self.blockchain().is_smart_contract(&caller)
+                || self.read_access_list().contains(&caller)
+                || self.has_paid_read_access(&caller),
+            "no read access"
+        );
+        Ok(())
+    }
+
+    fn has_paid_read_access(&self, reader: &Address) -> bool {
+        self.read_access_expiry(reader).get() > self.blockchain().get_block_epoch()
+    }
+
+    #[endpoint(setReadFee)]
+    fn set_read_fee(&self, fee_per_epoch: Self::BigUint) -> SCResult<()> {
+        only_governance!(self, b"setReadFee", fee_per_epoch);
+        self.read_fee().set(&fee_per_epoch);
+        Ok(())
+    }
+
+    /// Buys `epochs` epochs of read access for the calling contract, extending its
+    /// current access. The fees are added to the available funds, subsidizing the oracle
+    /// payments.
+    #[endpoint(buyReadAccess)]
+    #[payable("*")]
+    fn buy_read_access(
+        &self,
+        epochs: u64,
+        #[payment] payment: Self::BigUint,
+        #[payment_token] token: TokenIdentifier,
+    ) -> SCResult<()> {
+        let fee = self.read_fee().get();
+        require!(fee > Self::BigUint::zero(), "paid read access disabled");
+        require!(token == self.token_id().get(), "Wrong token type");
+        require!(epochs > 0, "epochs must be greater than 0");
+        require!(
+            payment == fee * Self::BigUint::from(epochs),
+            "payment does not match the read fee"
+        );
+
+        let caller = self.blockchain().get_caller();
+        let current_epoch = self.blockchain().get_block_epoch();
+        let starting_epoch = core::cmp::max(self.read_access_expiry(&caller).get(), current_epoch);
+        self.read_access_expiry(&caller).set(&(starting_epoch + epochs));
+        self.recorded_funds(&token)
+            .update(|recorded_funds| recorded_funds.available += payment);
+        Ok(())
+    }
+
+    /// Sends the latest answer to the `callback` endpoint of the calling contract, which
+    /// must have paid for read access (or be whitelisted), as an asynchronous call, so that
+    /// contracts in other shards can read it. The callback receives the round id followed
+    /// by the answer values, like the notifications of the subscribers.
+    #[endpoint(getLatestPrice)]
+    fn get_latest_price(&self, callback: BoxedBytes) -> SCResult<AsyncCall<Self::SendApi>> {
+        let caller = self.blockchain().get_caller();
+        require!(
+            self.read_access_list().contains(&caller) || self.has_paid_read_access(&caller),
+            "no read access"
+        );
+        let round_id = self.latest_round_id().get();
+        let answer = self
+            .rounds()
+            .get(&round_id)
+            .and_then(|round| round.answer)
+            .ok_or("no answer")?;
+        let mut contract_call =
+            ContractCall::<Self::SendApi, ()>::new(self.send(), caller, callback);
+        contract_call.arg_buffer = self.answer_arguments(round_id, &answer);
+        Ok(contract_call.async_call())
+    }
+
+    #[endpoint(setReadAccessCheck)]
+    fn set_read_access_check(&self, enabled: bool) -> SCResult<()> {
+        only_governance!(self, b"setReadAccessCheck", enabled);
+        self.read_access_check_enabled().set(&enabled);
+        Ok(())
+    }
+
+    #[endpoint(addReadAccess)]
+    fn add_read_access(&self, reader: Address) -> SCResult<()> {
+        only_governance!(self, b"addReadAccess", reader);
+        self.read_access_list().insert(reader);
+        Ok(())
+    }
+
+    #[endpoint(removeReadAccess)]
+    fn remove_read_access(&self, reader: Address) -> SCResult<()> {
+        only_governance!(self, b"removeReadAccess", reader);
+        require!(self.read_access_list().remove(&reader), "no read access");
+        Ok(())
+    }
+
+    #[view(hasReadAccess)]
+    fn has_read_access(&self, reader: Address) -> bool {
+        !self.read_access_check_enabled().get()
+            || !self.blockchain().is_smart_contract(&reader)
+            || self.read_access_list().contains(&reader)
+            || self.has_paid_read_access(&reader)
+    }
+
+    #[view(latestTimestamp)]
+    fn latest_timestamp(&self) -> u64 {
+        self.get_timestamp(self.latest_round_id().get())
+    }
+
+    /// The description, decimals and number of values of the feed, along with its latest
+    /// answer, in a single query.
+    #[view(getFeedInfo)]
+    fn get_feed_info(&self) -> SCResult<FeedInfo<Self::BigUint>> {
+        let latest_round_id = self.latest_round_id().get();
+        Ok(FeedInfo {
+            description: self.description().get(),
+            decimals: self.decimals().get(),
+            values_count: self.values_count().get(),
+            latest_round_id,
+            latest_answer: self.get_answer(latest_round_id)?.into_option(),
+            latest_timestamp: self.get_timestamp(latest_round_id),
+        })
+    }
+
+    #[view(secondsSinceLastUpdate)]
+    fn seconds_since_last_update(&self) -> u64 {
+        self.blockchain()
+            .get_block_timestamp()
+            .saturating_sub(self.latest_timestamp())
+    }
+
+    /// A feed which was never answered is always stale.
+    #[view(isAnswerStale)]
+    fn is_answer_stale(&self, max_age_seconds: u64) -> bool {
+        let answered = self
+            .rounds()
+            .get(&self.latest_round_id().get())
+            .map_or_else(|| false, |round| round.answer.is_some());
+        !answered || self.seconds_since_last_update() > max_age_seconds
+    }
+
+    #[view(latestRound)]
+    fn latest_round(&self) -> u64 {
+        self.latest_round_id().get()
+    }
+
+    #[view(withdrawablePayment)]
+    fn withdrawable_payment(
+        &self,
+        oracle: Address,
+        #[var_args] token_id: OptionalArg<TokenIdentifier>,
+    ) -> Self::BigUint {
+        self.oracle_withdrawable(&oracle, &self.token_or_payment_token(token_id))
+            .get()
+    }
+
+    /// The payments owed to each oracle because of insufficient funds.
+    #[view(getOwedPayments)]
+    fn get_owed_payments(
+        &self,
+        #[var_args] token_id: OptionalArg<TokenIdentifier>,
+    ) -> MultiResultVec<MultiArg2<Address, Self::BigUint>> {
+        let token_id = self.token_or_payment_token(token_id);
+        self.owed_payments(&token_id)
+            .iter()
+            .map(|(oracle, owed)| MultiArg2::from((oracle, owed)))
+            .collect()
+    }
+
+    /// The sum withdrawable by each of the oracles.
+    #[view(withdrawablePayments)]
+    fn withdrawable_payments(
+        &self,
+        #[var_args] token_id: OptionalArg<TokenIdentifier>,
+    ) -> MultiResultVec<MultiArg2<Address, Self::BigUint>> {
+        let token_id = self.token_or_payment_token(token_id);
+        self.oracles()
+            .keys()
+            .map(|oracle| {
+                let withdrawable = self.oracle_withdrawable(&oracle, &token_id).get();
+                MultiArg2::from((oracle, withdrawable))
+            })
+            .collect()
+    }
+
+    #[endpoint(withdrawPayment)]
+    fn withdraw_payment(
+        &self,
+        oracle: Address,
+        recipient: Address,
+        amount: Self::BigUint,
+        #[var_args] token_id: OptionalArg<TokenIdentifier>,
+    ) -> SCResult<()> {
+        self.withdraw_payment_internal(oracle, recipient, amount, token_id)
+    }
+
+    /// Withdraws to the oracle's payout address.
+    #[endpoint(withdrawPaymentToPayoutAddress)]
+    fn withdraw_payment_to_payout_address(
+        &self,
+        oracle: Address,
+        amount: Self::BigUint,
+        #[var_args] token_id: OptionalArg<TokenIdentifier>,
+    ) -> SCResult<()> {
+        let recipient = self.get_payout_address(oracle.clone())?;
+        self.withdraw_payment_internal(oracle, recipient, amount, token_id)
+    }
+
+    /// Withdraws to several recipients at once, e.g. to share the rewards with the
+    /// providers of the oracle's infrastructure. The amounts together cannot exceed the
+    /// withdrawable balance.
+    #[endpoint(withdrawPaymentSplit)]
+    fn withdraw_payment_split(
+        &self,
+        oracle: Address,
+        recipients: Vec<Address>,
+        amounts: Vec<Self::BigUint>,
+        #[var_args] token_id: OptionalArg<TokenIdentifier>,
+    ) -> SCResult<()> {
+        self.require_oracle_admin(&oracle)?;
+        self.settle_pending_earnings(&oracle);
+
+        require!(!recipients.is_empty(), "no recipients");
+        require!(
+            recipients.len() == amounts.len(),
+            "need same recipient and amount count"
+        );
+        let token_id = self.token_or_payment_token(token_id);
+        let mut total = Self::BigUint::zero();
+        for amount in amounts.iter() {
+            total += amount;
+        }
+        let withdrawable = self.oracle_withdrawable(&oracle, &token_id).get();
+        require!(withdrawable >= total, "insufficient withdrawable funds");
+
+        self.recorded_funds(&token_id)
+            .update(|recorded_funds| recorded_funds.allocated -= &total);
+        self.oracle_withdrawable(&oracle, &token_id)
+            .set(&(withdrawable - total));
+
+        for (recipient, amount) in recipients.iter().zip(amounts.iter()) {
+            self.send_funds(&token_id, recipient, amount, b"");
+        }
+        Ok(())
+    }
+
+    fn withdraw_payment_internal(
+        &self,
+        oracle: Address,
+        recipient: Address,
+        amount: Self::BigUint,
+        token_id: OptionalArg<TokenIdentifier>,
+    ) -> SCResult<()> {
+        self.require_oracle_admin(&oracle)?;
+        self.settle_pending_earnings(&oracle);
+
+        let token_id = self.token_or_payment_token(token_id);
+        let withdrawable = self.oracle_withdrawable(&oracle, &token_id).get();
+        require!(withdrawable >= amount, "insufficient withdrawable funds");
+
+        self.recorded_funds(&token_id)
+            .update(|recorded_funds| recorded_funds.allocated -= &amount);
+        self.oracle_withdrawable(&oracle, &token_id)
+            .set(&(withdrawable - amount.clone()));
+
+        self.send_funds(&token_id, &recipient, &amount, b"");
+        Ok(())
+    }
+
+    #[view(withdrawableAddedFunds)]
+    fn withdrawable_added_funds(
+        &self,
+        #[var_args] token_id: OptionalArg<TokenIdentifier>,
+    ) -> Self::BigUint {
+        self.get_deposit(
+            &self.token_or_payment_token(token_id),
+            &self.blockchain().get_caller(),
+        )
+    }
+
+    /// Funds in the payment token of future rounds can only be withdrawn as long as
+    /// enough remain to pay the oracles for the next rounds.
+    #[endpoint(withdrawFunds)]
+    fn withdraw_funds(
+        &self,
+        amount: Self::BigUint,
+        #[var_args] token_id: OptionalArg<TokenIdentifier>,
+    ) -> SCResult<()> {
+        self.require_not_paused()?;
+        self.settle_all_pending_earnings();
+        let token_id = self.token_or_payment_token(token_id);
+        let recorded_funds = self.get_recorded_funds(&token_id);
+        let caller = &self.blockchain().get_caller();
+        let caller_shares = self.get_deposit_shares(&token_id, caller);
+        let total_shares = self.total_deposit_shares(&token_id).get();
+        let deposit =
+            deposits::claim_for_shares(&caller_shares, &recorded_funds.available, &total_shares);
+        require!(amount <= deposit, "Insufficient funds to withdraw");
+        require!(
+            self.unreserved_funds(&token_id) >= amount,
+            "insufficient reserve funds"
+        );
+        let burned_shares =
+            deposits::shares_for_withdrawal(&amount, &recorded_funds.available, &total_shares);
+        self.set_deposit_shares(&token_id, caller, &(caller_shares - burned_shares.clone()));
+        self.total_deposit_shares(&token_id)
+            .set(&(total_shares - burned_shares));
+        self.recorded_funds(&token_id)
+            .update(|recorded_funds| recorded_funds.available -= &amount);
+        self.send_funds(&token_id, caller, &amount, b"withdraw");
+        Ok(())
+    }
+
+    /// Registers the ed25519 public key used to sign submissions on behalf of the oracle.
+    /// Calling it again rotates the key.
+    #[endpoint(setSigningKey)]
+    fn set_signing_key(&self, oracle: Address, signing_key: BoxedBytes) -> SCResult<()> {
+        self.require_oracle_admin(&oracle)?;
+        require!(signing_key.len() == 32, "invalid signing key length");
+        self.signing_keys().insert(oracle, signing_key);
+        Ok(())
+    }
+
+    #[endpoint(removeSigningKey)]
+    fn remove_signing_key(&self, oracle: Address) -> SCResult<()> {
+        self.require_oracle_admin(&oracle)?;
+        self.signing_keys().remove(&oracle);
+        Ok(())
+    }
+
+    #[view(getSigningKey)]
+    fn get_signing_key(&self, oracle: Address) -> OptionalResult<BoxedBytes> {
+        self.signing_keys().get(&oracle).into()
+    }
+
+    /// Allows `delegate` to call `submit` on behalf of the oracle, which is still the one
+    /// credited and paid for the submissions. This keeps the oracle's own key offline and
+    /// lets node keys be rotated without reconfiguring the oracles.
+    #[endpoint(addDelegate)]
+    fn add_delegate(&self, oracle: Address, delegate: Address) -> SCResult<()> {
+        self.require_oracle_admin(&oracle)?;
+        require!(
+            !self.oracles().contains_key(&delegate),
+            "an oracle cannot be a delegate"
+        );
+        require!(
+            !self.delegated_oracles().contains_key(&delegate),
+            "already a delegate"
+        );
+        self.delegates(&oracle).insert(delegate.clone());
+        self.delegated_oracles().insert(delegate, oracle);
+        Ok(())
+    }
+
+    #[endpoint(removeDelegate)]
+    fn remove_delegate(&self, oracle: Address, delegate: Address) -> SCResult<()> {
+        self.require_oracle_admin(&oracle)?;
+        require!(self.delegates(&oracle).remove(&delegate), "not a delegate");
+        self.delegated_oracles().remove(&delegate);
+        Ok(())
+    }
+
+    #[view(getDelegates)]
+    fn get_delegates(&self, oracle: Address) -> MultiResultVec<Address> {
+        self.delegates(&oracle).iter().collect()
+    }
+
+    /// The oracle the caller submits for: the oracle it is a delegate of, or itself.
+    fn submitting_oracle(&self) -> Address {
+        let caller = self.blockchain().get_caller();
+        self.delegated_oracles().get(&caller).unwrap_or(caller)
+    }
+
+    /// The registered signing key, or the oracle's own address (which is its public key)
+    /// if no signing key was registered.
+    fn oracle_signing_key(&self, oracle: &Address) -> BoxedBytes {
+        self.signing_keys()
+            .get(oracle)
+            .unwrap_or_else(|| BoxedBytes::from(oracle.as_bytes()))
+    }
+
+    fn require_oracle_admin(&self, oracle: &Address) -> SCResult<()> {
+        require!(
+            self.get_oracle_status_result(oracle)?.admin == self.blockchain().get_caller(),
+            "only callable by admin"
+        );
+        Ok(())
+    }
+
+    #[view(getAdmin)]
+    fn get_admin(&self, oracle: Address) -> SCResult<Address> {
+        Ok(self.get_oracle_status_result(&oracle)?.admin)
+    }
+
+    /// Lets the admin keep the oracle's payments apart from its own wallet.
+    #[endpoint(setPayoutAddress)]
+    fn set_payout_address(&self, oracle: Address, payout_address: Address) -> SCResult<()> {
+        self.require_oracle_admin(&oracle)?;
+        let mut oracle_status = self.get_oracle_status_result(&oracle)?;
+        oracle_status.payout_address = Some(payout_address);
+        self.oracles().insert(oracle, oracle_status);
+        Ok(())
+    }
+
+    /// The address receiving the oracle's payments, which is its admin unless set.
+    #[view(getPayoutAddress)]
+    fn get_payout_address(&self, oracle: Address) -> SCResult<Address> {
+        let oracle_status = self.get_oracle_status_result(&oracle)?;
+        Ok(oracle_status.payout_address.unwrap_or(oracle_status.admin))
+    }
+
+    #[endpoint(transferAdmin)]
+    fn transfer_admin(&self, oracle: Address, new_admin: Address) -> SCResult<()> {
+        let mut oracle_status = self.get_oracle_status_result(&oracle)?;
+        require!(
+            oracle_status.admin == self.blockchain().get_caller(),
+            "only callable by admin"
+        );
+        oracle_status.pending_admin = Some(new_admin);
+        self.oracles().insert(oracle, oracle_status);
+        Ok(())
+    }
+
+    #[endpoint(acceptAdmin)]
+    fn accept_admin(&self, oracle: Address) -> SCResult<()> {
+        let mut oracle_status = self.get_oracle_status_result(&oracle)?;
+        let caller = self.blockchain().get_caller();
+        require!(
+            oracle_status.pending_admin == Some(caller.clone()),
+            "only callable by pending admin"
+        );
+        oracle_status.pending_admin = None;
+        oracle_status.admin = caller;
+        self.oracles().insert(oracle, oracle_status);
+        Ok(())
+    }
+
+    /// Starts a new round, which times out after `timeout` seconds if given, instead of
+    /// the timeout of the feed. Governance can start rounds without being a requester.
+    #[endpoint(requestNewRound)]
+    fn request_new_round(&self, #[var_args] timeout: OptionalArg<u64>) -> SCResult<u64> {
+        self.require_not_paused()?;
+        let timeout = timeout.into_option();
+        let caller = self.blockchain().get_caller();
+        let is_requester = self
+            .requesters()
+            .get(&caller)
+            .map_or_else(|| false, |requester| requester.authorized);
+        if !is_requester {
+            only_governance!(self, b"requestNewRound", timeout);
+        }
+
+        let current = self.reporting_round_id().get();
+        require!(
+            self.rounds()
+                .get(&current)
+                .map_or_else(|| false, |round| round.updated_at > 0)
+                || self.timed_out(&current)?,
+            "prev round must be supersedable"
+        );
+
+        self.require_below_unanswered_rounds_limit()?;
+        let new_round_id = current + 1;
+        if is_requester {
+            self.requester_initialize_new_round(new_round_id)?;
+        } else {
+            self.initialize_new_round(&new_round_id)?;
+        }
+        if let Some(timeout) = timeout {
+            require!(timeout > 0, "timeout must be greater than 0");
+            let mut details = self.get_round_details(&new_round_id)?;
+            details.timeout = timeout;
+            self.details().insert(new_round_id, details);
+        }
+        Ok(new_round_id)
+    }
+
+    /// Changes the decimals, description and number of values of the feed, starting with
+    /// the next round. Every round keeps the parameters in force when it started. The
+    /// decimals of each value are reset unless given again.
+    #[endpoint(reconfigureFeed)]
+    fn reconfigure_feed(
+        &self,
+        decimals: u8,
+        description: BoxedBytes,
+        values_count: usize,
+        #[var_args] value_decimals: OptionalArg<Vec<u8>>,
+    ) -> SCResult<()> {
+        let value_decimals = value_decimals.into_option().unwrap_or_default();
+        only_governance!(
+            self,
+            b"reconfigureFeed",
+            decimals,
+            description,
+            values_count,
+            value_decimals,
+        );
+        require!(values_count > 0, "values count must be greater than 0");
+        self.require_valid_value_decimals(&value_decimals, values_count)?;
+        self.pending_feed_config().set(&FeedConfig {
+            decimals,
+            description,
+            values_count,
+            value_decimals,
+        });
+        Ok(())
+    }
+
+    fn require_valid_value_decimals(
+        &self,
+        value_decimals: &[u8],
+        values_count: usize,
+    ) -> SCResult<()> {
+        require!(
+            value_decimals.is_empty() || value_decimals.len() == values_count,
+            "incorrect number of value decimals"
+        );
+        Ok(())
+    }
+
+    /// The decimals of the value at `index` and the matching scale factor (10^decimals).
+    #[view(getValueMetadata)]
+    fn get_value_metadata(&self, index: usize) -> SCResult<MultiArg2<u8, Self::BigUint>> {
+        require!(index < self.values_count().get(), "invalid value index");
+        let decimals = self.decimals_of_value(index);
+        Ok(MultiArg2::from((decimals, scaling::pow10(decimals))))
+    }
+
+    fn decimals_of_value(&self, index: usize) -> u8 {
+        self.value_decimals()
+            .get()
+            .get(index)
+            .cloned()
+            .unwrap_or_else(|| self.decimals().get())
+    }
+
+    #[view(getPendingFeedConfig)]
+    fn get_pending_feed_config(&self) -> OptionalResult<FeedConfig> {
+        if self.pending_feed_config().is_empty() {
+            OptionalResult::None
+        } else {
+            OptionalResult::Some(self.pending_feed_config().get())
+        }
+    }
+
+    /// Changes the range of accepted submission values, starting with the next round.
+    #[endpoint(setSubmissionBounds)]
+    fn set_submission_bounds(
+        &self,
+        min_submission_value: Self::BigUint,
+        max_submission_value: Self::BigUint,
+    ) -> SCResult<()> {
+        only_governance!(
+            self,
+            b"setSubmissionBounds",
+            min_submission_value,
+            max_submission_value,
+        );
+        require!(
+            min_submission_value <= max_submission_value,
+            "min_submission_value cannot exceed max_submission_value"
+        );
+        self.pending_submission_bounds().set(&SubmissionBounds {
+            min: min_submission_value,
+            max: max_submission_value,
+        });
+        Ok(())
+    }
+
+    /// Accepts negative values down to `-bound`, starting with the next round; 0 only
+    /// accepts non-negative values. While negative values are accepted, they replace
+    /// `min_submission_value` as the lower bound.
+    #[endpoint(setNegativeValueBound)]
+    fn set_negative_value_bound(&self, bound: Self::BigUint) -> SCResult<()> {
+        only_governance!(self, b"setNegativeValueBound", bound);
+        self.negative_value_bound().set(&bound);
+        Ok(())
+    }
+
+    #[view(getSubmissionBounds)]
+    fn get_submission_bounds(&self) -> MultiArg2<Self::BigUint, Self::BigUint> {
+        MultiArg2::from((
+            self.min_submission_value().get(),
+            self.max_submission_value().get(),
+        ))
+    }
+
+    /// The whole configuration of the feed in a single query.
+    #[view(getConfig)]
+    fn get_config(&self) -> AggregatorConfig<Self::BigUint> {
+        AggregatorConfig {
+            payment_amount: self.payment_amount().get(),
+            min_submissions: self.min_submission_count().get(),
+            max_submissions: self.max_submission_count().get(),
+            restart_delay: self.restart_delay().get(),
+            timeout: self.timeout().get(),
+            min_submission_value: self.min_submission_value().get(),
+            max_submission_value: self.max_submission_value().get(),
+            decimals: self.decimals().get(),
+            description: self.description().get(),
+            values_count: self.values_count().get(),
+            token_id: self.token_id().get(),
+        }
+    }
+
+    #[view(getPendingSubmissionBounds)]
+    fn get_pending_submission_bounds(&self) -> OptionalResult<SubmissionBounds<Self::BigUint>> {
+        if self.pending_submission_bounds().is_empty() {
+            OptionalResult::None
+        } else {
+            OptionalResult::Some(self.pending_submission_bounds().get())
+        }
+    }
+
+    #[endpoint(setAggregationStrategy)]
+    fn set_aggregation_strategy(&self, strategy: AggregationStrategy) -> SCResult<()> {
+        only_governance!(self, b"setAggregationStrategy", strategy);
+        self.aggregation_strategy().set(&strategy);
+        Ok(())
+    }
+
+    /// Feeds deployed without an explicit strategy use the median.
+    #[view(getAggregationStrategy)]
+    fn get_aggregation_strategy(&self) -> AggregationStrategy {
+        if self.aggregation_strategy().is_empty() {
+            AggregationStrategy::Median
+        } else {
+            self.aggregation_strategy().get()
+        }
+    }
+
+    /// Percentage of the submissions discarded from each end by the trimmed mean.
+    #[endpoint(setTrimPercent)]
+    fn set_trim_percent(&self, trim_percent: u8) -> SCResult<()> {
+        only_governance!(self, b"setTrimPercent", trim_percent);
+        require!(
+            trim_percent > 0 && trim_percent < 50,
+            "trim percent must be between 1 and 49"
+        );
+        self.trim_percent().set(&trim_percent);
+        Ok(())
+    }
+
+    #[view(getTrimPercent)]
+    fn get_trim_percent(&self) -> u8 {
+        if self.trim_percent().is_empty() {
+            DEFAULT_TRIM_PERCENT
+        } else {
+            self.trim_percent().get()
+        }
+    }
+
+    /// Sets the number of rounds of oracle payments which the available funds must cover.
+    #[endpoint(setReserveRounds)]
+    fn set_reserve_rounds(&self, reserve_rounds: u64) -> SCResult<()> {
+        only_governance!(self, b"setReserveRounds", reserve_rounds);
+        require!(
+            reserve_rounds > 0 && reserve_rounds <= MAX_RESERVE_ROUNDS,
+            "reserve rounds must be between 1 and 10"
+        );
+        self.reserve_rounds().set(&reserve_rounds);
+        Ok(())
+    }
+
+    #[view(getReserveRounds)]
+    fn get_reserve_rounds(&self) -> u64 {
+        if self.reserve_rounds().is_empty() {
+            DEFAULT_RESERVE_ROUNDS
+        } else {
+            self.reserve_rounds().get()
+        }
+    }
+
+    /// The threshold is expressed in 1/100_000 units (1% == 1_000); 0 disables flagging.
+    #[endpoint(setDeviationFlagging)]
+    fn set_deviation_flagging(&self, flags_address: Address, threshold: u64) -> SCResult<()> {
+        only_governance!(self, b"setDeviationFlagging", flags_address, threshold);
+        self.flags_address().set(&flags_address);
+        self.flagging_threshold().set(&threshold);
+        Ok(())
+    }
+
+    /// Submissions deviating from the latest answer by more than `max_deviation`
+    /// (in 1/100_000 units, 0 disables the check) are either rejected or accepted and
+    /// reported through a `submission_deviation` event.
+    #[endpoint(setMaxSubmissionDeviation)]
+    fn set_max_submission_deviation(&self, max_deviation: u64, reject: bool) -> SCResult<()> {
+        only_governance!(self, b"setMaxSubmissionDeviation", max_deviation, reject);
+        self.max_submission_deviation().set(&max_deviation);
+        self.reject_deviating_submissions().set(&reject);
+        Ok(())
+    }
+
+    /// Answers whose submissions spread (max - min) by more than `max_dispersion` of any
+    /// of the answer values (in 1/100_000 units, 0 disables the check) are withheld and
+    /// their round is marked as disputed.
+    #[endpoint(setMaxDispersion)]
+    fn set_max_dispersion(&self, max_dispersion: u64) -> SCResult<()> {
+        only_governance!(self, b"setMaxDispersion", max_dispersion);
+        self.max_dispersion().set(&max_dispersion);
+        Ok(())
+    }
+
+    /// When enabled, the payments of each oracle are added to its withdrawable balance
+    /// once per epoch instead of on every submission. Disabling it settles the pending
+    /// earnings of all the oracles.
+    #[endpoint(setBatchedPayouts)]
+    fn set_batched_payouts(&self, enabled: bool) -> SCResult<()> {
+        only_governance!(self, b"setBatchedPayouts", enabled);
+        if !enabled {
+            self.settle_all_pending_earnings();
+        }
+        self.batched_payouts().set(&enabled);
+        Ok(())
+    }
+
+    /// Adds the pending earnings of the oracles to their withdrawable balances.
+    #[endpoint(settleEarnings)]
+    fn settle_earnings(&self) {
+        self.settle_all_pending_earnings();
+    }
+
+    #[view(getPendingEarnings)]
+    fn get_pending_earnings(
+        &self,
+        oracle: Address,
+    ) -> OptionalResult<MultiArg3<u64, TokenIdentifier, Self::BigUint>> {
+        if self.pending_earnings(&oracle).is_empty() {
+            return OptionalResult::None;
+        }
+        let earnings = self.pending_earnings(&oracle).get();
+        OptionalResult::Some(MultiArg3::from((
+            earnings.epoch,
+            earnings.token_id,
+            earnings.amount,
+        )))
+    }
+
+    /// When enabled, the oracles are paid for their submissions only once the round is
+    /// answered; the payments held for rounds which end without an answer go back to
+    /// the available funds.
+    #[endpoint(setEscrowPayments)]
+    fn set_escrow_payments(&self, enabled: bool) -> SCResult<()> {
+        only_governance!(self, b"setEscrowPayments", enabled);
+        self.escrow_payments().set(&enabled);
+        Ok(())
+    }
+
+    #[view(isRoundDisputed)]
+    fn is_round_disputed(&self, round_id: u64) -> bool {
+        self.disputed_rounds().contains(&round_id)
+    }
+
+    #[endpoint(setTimeoutPolicy)]
+    fn set_timeout_policy(&self, policy: TimeoutPolicy) -> SCResult<()> {
+        only_governance!(self, b"setTimeoutPolicy", policy);
+        self.timeout_policy().set(&policy);
+        Ok(())
+    }
+
+    /// Feeds deployed without an explicit policy carry the previous answer forward.
+    #[view(getTimeoutPolicy)]
+    fn get_timeout_policy(&self) -> TimeoutPolicy {
+        if self.timeout_policy().is_empty() {
+            TimeoutPolicy::CarryForward
+        } else {
+            self.timeout_policy().get()
+        }
+    }
+
+    #[view(isRoundStale)]
+    fn is_round_stale(&self, round_id: u64) -> bool {
+        self.stale_rounds().contains(&round_id)
+    }
+
+    /// Lets new rounds start again after the feed was halted by a timed out round, which
+    /// is closed without an answer if it is still the reporting round.
+    #[endpoint(resumeRounds)]
+    fn resume_rounds(&self) -> SCResult<()> {
+        only_governance!(self, b"resumeRounds");
+        let current = self.reporting_round_id().get();
+        if self.details().contains_key(&current) && self.timed_out(&current)? {
+            let mut round = self.get_round(&current)?;
+            self.mark_round_stale(&mut round);
+            round.updated_at = self.blockchain().get_block_timestamp();
+            self.rounds().insert(current, round);
+            self.remove_round_details(current);
+        }
+        self.halted().set(&false);
+        self.rounds_resumed_event(current);
+        Ok(())
+    }
+
+    fn check_submission_deviation(
+        &self,
+        submission: &Submission<Self::BigUint>,
+        round_id: u64,
+        oracle: &Address,
+    ) -> SCResult<()> {
+        let max_deviation = self.max_submission_deviation().get();
+        if max_deviation == 0 {
+            return Ok(());
+        }
+        let latest_answer = self
+            .rounds()
+            .get(&self.latest_round_id().get())
+            .and_then(|round| round.answer);
+        if let Some(latest_answer) = latest_answer {
+            if deviation::submission_exceeds_threshold(&latest_answer, submission, max_deviation)
+            {
+                require!(
+                    !self.reject_deviating_submissions().get(),
+                    "submission deviates too much from the latest answer"
+                );
+                self.submission_deviation_event(round_id, oracle, submission);
+            }
+        }
+        Ok(())
+    }
+
+    /// Limits the number of rounds which can be started without any of them being
+    /// answered; 0 disables the limit. The owner can still force new rounds.
+    #[endpoint(setMaxUnansweredRounds)]
+    fn set_max_unanswered_rounds(&self, max_unanswered_rounds: u64) -> SCResult<()> {
+        only_governance!(self, b"setMaxUnansweredRounds", max_unanswered_rounds);
+        self.max_unanswered_rounds().set(&max_unanswered_rounds);
+        Ok(())
+    }
+
+    /// The number of rounds started since the latest answered round.
+    #[view(getUnansweredRounds)]
+    fn get_unanswered_rounds(&self) -> u64 {
+        self.reporting_round_id()
+            .get()
+            .saturating_sub(self.latest_round_id().get())
+    }
+
+    fn require_below_unanswered_rounds_limit(&self) -> SCResult<()> {
+        let max_unanswered_rounds = self.max_unanswered_rounds().get();
+        require!(
+            max_unanswered_rounds == 0 || self.get_unanswered_rounds() < max_unanswered_rounds,
+            "too many unanswered rounds"
+        );
+        Ok(())
+    }
+
+    /// Number of rounds kept in storage when new rounds are created; 0 keeps all rounds.
+    #[endpoint(setRoundRetention)]
+    fn set_round_retention(&self, retention: u64) -> SCResult<()> {
+        only_governance!(self, b"setRoundRetention", retention);
+        self.round_retention().set(&retention);
+        Ok(())
+    }
+
+    #[endpoint(pruneRounds)]
+    fn prune_rounds(&self, up_to_round: u64) -> SCResult<u64> {
+        only_governance!(self, b"pruneRounds", up_to_round);
+        Ok(self.prune_rounds_internal(up_to_round, ROUND_MAX))
+    }
+
+    /// Removes the rounds (and their details) up to and including the given round.
+    /// The latest answered round and the rounds still needed to validate submissions
+    /// are never removed. Returns the number of removed rounds.
+    fn prune_rounds_internal(&self, up_to_round: u64, max_count: u64) -> u64 {
+        let first_kept_round = core::cmp::min(
+            self.latest_round_id().get(),
+            self.reporting_round_id().get().saturating_sub(2),
+        );
+        let oldest_round_id = self.oldest_round_id().get();
+        let end = core::cmp::min(up_to_round.saturating_add(1), first_kept_round);
+        if end <= oldest_round_id {
+            return 0;
+        }
+        let end = core::cmp::min(end, oldest_round_id.saturating_add(max_count));
+        for round_id in oldest_round_id..end {
+            self.rounds().remove(&round_id);
+            self.remove_round_details(round_id);
+            self.round_submissions(round_id).clear();
+            self.disputed_rounds().remove(&round_id);
+            self.stale_rounds().remove(&round_id);
+        }
+        self.oldest_round_id().set(&end);
+        end - oldest_round_id
+    }
+
+    #[endpoint]
+    fn pause(&self) -> SCResult<()> {
+        only_governance!(self, b"pause");
+        self.paused().set(&true);
+        Ok(())
+    }
+
+    #[endpoint]
+    fn unpause(&self) -> SCResult<()> {
+        only_governance!(self, b"unpause");
+        self.paused().set(&false);
+        Ok(())
+    }
+
+    fn require_not_paused(&self) -> SCResult<()> {
+        self.require_storage_migrated()?;
+        require!(!self.paused().get(), "contract is paused");
+        Ok(())
+    }
+
+    /// Starts a new round if the latest answer is older than the heartbeat interval.
+    /// The caller is paid the stale round incentive, if the available funds cover it
+    /// on top of the required reserve.
+    #[endpoint(startRoundIfStale)]
+    fn start_round_if_stale(&self) -> SCResult<u64> {
+        self.require_not_paused()?;
+        let heartbeat = self.heartbeat().get();
+        require!(heartbeat > 0, "heartbeat not configured");
+        require!(
+            self.latest_timestamp() + heartbeat < self.blockchain().get_block_timestamp(),
+            "answer is not stale"
+        );
+
+        let current = self.reporting_round_id().get();
+        require!(
+            self.supersedable(&current)?,
+            "prev round must be supersedable"
+        );
+        self.require_below_unanswered_rounds_limit()?;
+        let new_round_id = current + 1;
+        self.initialize_new_round(&new_round_id)?;
+        self.pay_stale_round_incentive();
+        Ok(new_round_id)
+    }
+
+    fn pay_stale_round_incentive(&self) {
+        let incentive = self.stale_round_incentive().get();
+        if incentive == Self::BigUint::zero() {
+            return;
+        }
+        let token_id = self.token_id().get();
+        let recorded_funds = self.recorded_funds(&token_id).get();
+        let required = self.required_reserve(&self.payment_amount().get()) + incentive.clone();
+        if recorded_funds.available < required {
+            return;
+        }
+        self.recorded_funds(&token_id)
+            .update(|recorded_funds| recorded_funds.available -= &incentive);
+        self.send_funds(
+            &token_id,
+            &self.blockchain().get_caller(),
+            &incentive,
+            b"stale round incentive",
+        );
+    }
+
+    #[endpoint(setHeartbeat)]
+    fn set_heartbeat(&self, heartbeat: u64, incentive: Self::BigUint) -> SCResult<()> {
+        only_governance!(self, b"setHeartbeat", heartbeat, incentive);
+        self.heartbeat().set(&heartbeat);
+        self.stale_round_incentive().set(&incentive);
+        Ok(())
+    }
+
+    /// The first `bonus_submissions` oracles to submit in a round are paid `bonus_amount`
+    /// on top of the regular payment. Applies to rounds started after the change.
+    #[endpoint(setEarlySubmissionBonus)]
+    fn set_early_submission_bonus(
+        &self,
+        bonus_submissions: u64,
+        bonus_amount: Self::BigUint,
+    ) -> SCResult<()> {
+        only_governance!(self, b"setEarlySubmissionBonus", bonus_submissions, bonus_amount);
+        let recorded_funds = self.recorded_funds(&self.token_id().get()).get();
+        require!(
+            recorded_funds.available
+                >= self.required_reserve_with_bonus(
+                    &self.payment_amount().get(),
+                    bonus_submissions,
+                    &bonus_amount
+                ),
+            "insufficient funds for payment"
+        );
+        self.bonus_submissions().set(&bonus_submissions);
+        self.bonus_amount().set(&bonus_amount);
+        Ok(())
+    }
+
+    /// The available funds which are not needed to pay the oracles for the next rounds.
+    /// Only the payment token of future rounds has a reserve.
+    fn unreserved_funds(&self, token_id: &TokenIdentifier) -> Self::BigUint {
+        let available = self.get_recorded_funds(token_id).available;
+        if token_id != &self.token_id().get() {
+            return available;
+        }
+        let reserve = self.required_reserve(&self.payment_amount().get());
+        if available > reserve {
+            available - reserve
+        } else {
+            Self::BigUint::zero()
+        }
+    }
+
+    #[view(getEmergencyWithdrawDelay)]
+    fn get_emergency_withdraw_delay(&self) -> u64 {
+        if self.emergency_withdraw_delay().is_empty() {
+            DEFAULT_EMERGENCY_WITHDRAW_DELAY
+        } else {
+            self.emergency_withdraw_delay().get()
+        }
+    }
+
+    /// The delay can only be increased, so that a compromised owner cannot skip it.
+    #[endpoint(setEmergencyWithdrawDelay)]
+    fn set_emergency_withdraw_delay(&self, delay: u64) -> SCResult<()> {
+        only_governance!(self, b"setEmergencyWithdrawDelay", delay);
+        require!(
+            delay >= self.get_emergency_withdraw_delay(),
+            "delay can only be increased"
+        );
+        self.emergency_withdraw_delay().set(&delay);
+        Ok(())
+    }
+
+    /// Schedules a withdrawal of funds which are not reserved for oracle payments. It can
+    /// only be executed after the emergency withdraw delay, which gives sponsors and
+    /// oracles time to withdraw their own funds. Replaces any pending withdrawal.
+    #[endpoint(scheduleEmergencyWithdraw)]
+    fn schedule_emergency_withdraw(
+        &self,
+        token_id: TokenIdentifier,
+        amount: Self::BigUint,
+        recipient: Address,
+    ) -> SCResult<()> {
+        only_governance!(self, b"scheduleEmergencyWithdraw", token_id, amount, recipient);
+        self.settle_all_pending_earnings();
+        require!(
+            self.unreserved_funds(&token_id) >= amount,
+            "insufficient reserve funds"
+        );
+        let execute_after =
+            self.blockchain().get_block_timestamp() + self.get_emergency_withdraw_delay();
+        self.emergency_withdraw_scheduled_event(&token_id, &recipient, execute_after, &amount);
+        self.pending_emergency_withdrawal().set(&EmergencyWithdrawal {
+            token_id,
+            amount,
+            recipient,
+            execute_after,
+        });
+        Ok(())
+    }
+
+    #[endpoint(cancelEmergencyWithdraw)]
+    fn cancel_emergency_withdraw(&self) -> SCResult<()> {
+        only_governance!(self, b"cancelEmergencyWithdraw");
+        require!(
+            !self.pending_emergency_withdrawal().is_empty(),
+            "no pending emergency withdrawal"
+        );
+        self.pending_emergency_withdrawal().clear();
+        Ok(())
+    }
+
+    /// The reserve is checked again, since the available funds may have changed while
+    /// the withdrawal was pending.
+    #[endpoint(executeEmergencyWithdraw)]
+    fn execute_emergency_withdraw(&self) -> SCResult<()> {
+        only_governance!(self, b"executeEmergencyWithdraw");
+        self.settle_all_pending_earnings();
+        require!(
+            !self.pending_emergency_withdrawal().is_empty(),
+            "no pending emergency withdrawal"
+        );
+        let withdrawal = self.pending_emergency_withdrawal().get();
+        require!(
+            self.blockchain().get_block_timestamp() >= withdrawal.execute_after,
+            "emergency withdrawal still timelocked"
+        );
+        require!(
+            self.unreserved_funds(&withdrawal.token_id) >= withdrawal.amount,
+            "insufficient reserve funds"
+        );
+        self.pending_emergency_withdrawal().clear();
+        self.recorded_funds(&withdrawal.token_id)
+            .update(|recorded_funds| recorded_funds.available -= &withdrawal.amount);
+        self.send_funds(
+            &withdrawal.token_id,
+            &withdrawal.recipient,
+            &withdrawal.amount,
+            b"emergency withdraw",
+        );
+        self.emergency_withdraw_executed_event(
+            &withdrawal.token_id,
+            &withdrawal.recipient,
+            &withdrawal.amount,
+        );
+        Ok(())
+    }
+
+    #[view(getPendingEmergencyWithdrawal)]
+    fn get_pending_emergency_withdrawal(
+        &self,
+    ) -> OptionalResult<EmergencyWithdrawal<Self::BigUint>> {
+        if self.pending_emergency_withdrawal().is_empty() {
+            OptionalResult::None
+        } else {
+            OptionalResult::Some(self.pending_emergency_withdrawal().get())
+        }
+    }
+
+    /// Closes a timed out round right away, instead of waiting for the next round to
+    /// start. If the round received enough submissions its answer is kept, otherwise the
+    /// previous answer is carried over. No further submissions are accepted afterwards.
+    #[endpoint(closeTimedOutRound)]
+    fn close_timed_out_round(&self, round_id: u64) -> SCResult<()> {
+        let caller = self.blockchain().get_caller();
+        let is_requester = self
+            .requesters()
+            .get(&caller)
+            .map_or_else(|| false, |requester| requester.authorized);
+        if !is_requester {
+            only_governance!(self, b"closeTimedOutRound", round_id);
+        }
+        let details = self.get_round_details(&round_id)?;
+        require!(self.timed_out(&round_id)?, "round not timed out");
+
+        if self.submission_count(round_id) >= details.min_submissions {
+            self.remove_round_details(round_id);
+        } else {
+            self.update_timed_out_round_info(round_id)?;
+        }
+        Ok(())
+    }
+
+    /// Recovers a stuck feed: closes the reporting round whatever its state, carrying the
+    /// previous answer over if it was not answered, and starts a new round.
+    #[endpoint(ownerForceNewRound)]
+    fn owner_force_new_round(&self) -> SCResult<u64> {
+        only_governance!(self, b"ownerForceNewRound");
+        let current = self.reporting_round_id().get();
+        if self.details().contains_key(&current) {
+            self.force_close_round(current)?;
+        }
+
+        let new_round_id = current + 1;
+        self.initialize_new_round(&new_round_id)?;
+        self.new_round_forced_event(current, new_round_id);
+        Ok(new_round_id)
+    }
+
+    /// Hands governance over to a set of governors, `threshold` of which have to approve
+    /// every privileged action. An empty set gives governance back to the owner.
+    #[endpoint(setGovernors)]
+    fn set_governors(&self, governors: Vec<Address>, threshold: u64) -> SCResult<()> {
+        only_governance!(self, b"setGovernors", governors, threshold);
+        if governors.is_empty() {
+            require!(threshold == 0, "threshold must be 0 without governors");
+        } else {
+            require!(threshold > 0, "threshold must be greater than 0");
+            require!(
+                threshold <= governors.len() as u64,
+                "threshold cannot exceed governor count"
+            );
+        }
+        let previous: Vec<Address> = self.governors().iter().collect();
+        for governor in previous.iter() {
+            self.governors().remove(governor);
+        }
+        for governor in governors.into_iter() {
+            self.governors().insert(governor);
+        }
+        self.approval_threshold().set(&threshold);
+        Ok(())
     }
 
-    #[endpoint(transferAdmin)]
-    fn transfer_admin(&self, oracle: Address, new_admin: Address) -> SCResult<()> {
-        let mut oracle_status = self.get_oracle_status_result(&oracle)?;
-        require!(
-            oracle_status.admin == self.blockchain().get_caller(),
-            "only callable by admin"
-        );
-        oracle_status.pending_admin = Some(new_admin);
-        self.oracles().insert(oracle, oracle_status);
+    #[endpoint(approveAction)]
+    fn approve_action(&self, action_hash: H256) -> SCResult<()> {
+        let caller = self.blockchain().get_caller();
+        require!(self.governors().contains(&caller), "only governors may approve");
+        self.action_approvals(&action_hash).insert(caller);
         Ok(())
     }
 
-    #[endpoint(acceptAdmin)]
-    fn accept_admin(&self, oracle: Address) -> SCResult<()> {
-        let mut oracle_status = self.get_oracle_status_result(&oracle)?;
+    #[endpoint(revokeApproval)]
+    fn revoke_approval(&self, action_hash: H256) -> SCResult<()> {
         let caller = self.blockchain().get_caller();
         require!(
-            oracle_status.pending_admin == Some(caller.clone()),
-            "only callable by pending admin"
+            self.action_approvals(&action_hash).remove(&caller),
+            "action not approved by caller"
         );
-        oracle_status.pending_admin = None;
-        oracle_status.admin = caller;
-        self.oracles().insert(oracle, oracle_status);
         Ok(())
     }
 
-    #[endpoint(requestNewRound)]
-    fn request_new_round(&self) -> SCResult<u64> {
-        let requester_option = self.requesters().get(&self.blockchain().get_caller());
+    #[view(getGovernors)]
+    fn get_governors(&self) -> MultiResultVec<Address> {
+        self.governors().iter().collect()
+    }
+
+    #[view(getActionApprovals)]
+    fn get_action_approvals(&self, action_hash: H256) -> MultiResultVec<Address> {
+        self.action_approvals(&action_hash).iter().collect()
+    }
+
+    /// The approvals are consumed, so an approved action can only be executed once.
+    /// Approvals of former governors are not counted.
+    fn require_governance(&self, action: &[u8]) -> SCResult<()> {
+        self.require_storage_migrated()?;
+        let caller = self.blockchain().get_caller();
+        if self.governors().is_empty() {
+            require!(
+                caller == self.blockchain().get_owner_address(),
+                "Only owner may call this function!"
+            );
+            return Ok(());
+        }
         require!(
-            requester_option.map_or_else(|| false, |requester| requester.authorized),
-            "not authorized requester"
+            self.governors().contains(&caller),
+            "Only governors may call this function!"
         );
-
-        let current = self.reporting_round_id().get();
+        let action_hash = self.crypto().keccak256(action);
+        let approvers: Vec<Address> = self.action_approvals(&action_hash).iter().collect();
+        let approvals = approvers
+            .iter()
+            .filter(|approver| self.governors().contains(approver))
+            .count() as u64;
         require!(
-            self.rounds()
-                .get(&current)
-                .map_or_else(|| false, |round| round.updated_at > 0)
-                || self.timed_out(&current)?,
-            "prev round must be supersedable"
+            approvals >= self.approval_threshold().get(),
+            "not enough approvals"
         );
-
-        let new_round_id = current + 1;
-        self.requester_initialize_new_round(new_round_id)?;
-        Ok(new_round_id)
+        for approver in approvers.iter() {
+            self.action_approvals(&action_hash).remove(approver);
+        }
+        Ok(())
     }
 
     #[endpoint(setRequesterPermissions)]
@@ -397,7 +3569,7 @@ pub trait Aggregator {
         authorized: bool,
         delay: u64,
     ) -> SCResult<()> {
-        only_owner!(self, "Only owner may call this function!");
+        only_governance!(self, b"setRequesterPermissions", requester, authorized, delay);
         if authorized {
             self.requesters().insert(
                 requester,
@@ -413,6 +3585,33 @@ pub trait Aggregator {
         Ok(())
     }
 
+    /// The authorized requesters, with their delay and the last round each one started.
+    #[view(listRequesters)]
+    fn list_requesters(&self) -> MultiResultVec<MultiArg3<Address, u64, u64>> {
+        self.requesters()
+            .iter()
+            .map(|(address, requester)| {
+                MultiArg3::from((address, requester.delay, requester.last_started_round))
+            })
+            .collect()
+    }
+
+    /// The `oracleRoundState` of every enabled oracle.
+    #[view(oracleRoundStates)]
+    fn oracle_round_states(
+        &self,
+        queried_round_id: u64,
+    ) -> SCResult<MultiResultVec<MultiArg2<Address, OracleRoundState<Self::BigUint>>>> {
+        let mut states = Vec::new();
+        for oracle in self.oracles().keys() {
+            if self.oracle_enabled(&oracle) {
+                let state = self.oracle_round_state(oracle.clone(), queried_round_id)?;
+                states.push(MultiArg2::from((oracle, state)));
+            }
+        }
+        Ok(MultiResultVec::from(states))
+    }
+
     #[view(oracleRoundState)]
     fn oracle_round_state(
         &self,
@@ -426,7 +3625,12 @@ pub trait Aggregator {
         let round = self.get_round(&queried_round_id)?;
         let details = self.get_round_details(&queried_round_id)?;
         let oracle_status = self.get_oracle_status_result(&oracle)?;
-        let recorded_funds = self.recorded_funds().get();
+        let (payment_token, payment_amount) = if round.started_at > 0 {
+            (details.payment_token, details.payment_amount)
+        } else {
+            (self.token_id().get(), self.payment_amount().get())
+        };
+        let recorded_funds = self.get_recorded_funds(&payment_token);
         Ok(OracleRoundState {
             eligible_to_submit,
             round_id: queried_round_id,
@@ -435,19 +3639,40 @@ pub trait Aggregator {
             timeout: details.timeout,
             available_funds: recorded_funds.available,
             oracle_count: self.oracle_count(),
-            payment_amount: if round.started_at > 0 {
-                details.payment_amount
-            } else {
-                self.payment_amount().get()
-            },
+            payment_amount: self.oracle_payment(&oracle, payment_amount),
         })
     }
 
     fn initialize_new_round(&self, round_id: &u64) -> SCResult<()> {
         if let Some(last_round) = round_id.checked_sub(1) {
+            // the halt would be reverted along with the failing round start, so under the
+            // Halt policy a timed out round has to be closed with `closeTimedOutRound`
+            require!(
+                last_round == 0
+                    || self.get_timeout_policy() != TimeoutPolicy::Halt
+                    || !self.timed_out(&last_round)?,
+                "timed out round must be closed first"
+            );
             self.update_timed_out_round_info(last_round)?;
         }
+        require!(!self.halted().get(), "feed halted by a timed out round");
 
+        if !self.pending_feed_config().is_empty() {
+            let feed_config = self.pending_feed_config().get();
+            self.decimals().set(&feed_config.decimals);
+            self.description().set(&feed_config.description);
+            self.values_count().set(&feed_config.values_count);
+            self.value_decimals().set(&feed_config.value_decimals);
+            self.pending_feed_config().clear();
+        }
+        if !self.pending_submission_bounds().is_empty() {
+            let bounds = self.pending_submission_bounds().get();
+            self.min_submission_value().set(&bounds.min);
+            self.max_submission_value().set(&bounds.max);
+            self.pending_submission_bounds().clear();
+        }
+
+        let started_at = self.blockchain().get_block_timestamp();
         self.reporting_round_id().set(round_id);
         self.rounds().insert(
             round_id.clone(),
@@ -456,41 +3681,56 @@ pub trait Aggregator {
                 answer: None,
                 decimals: self.decimals().get(),
                 description: self.description().get(),
-                started_at: self.blockchain().get_block_timestamp(),
-                updated_at: self.blockchain().get_block_timestamp(),
+                started_at,
+                updated_at: started_at,
                 answered_in_round: 0,
+                dispersion: Vec::new(),
+                min_observation_age: 0,
+                median_observation_age: 0,
+                max_observation_age: 0,
+                fresh: false,
             },
         );
         self.details().insert(
             round_id.clone(),
             RoundDetails {
-                submissions: Vec::new(),
                 max_submissions: self.max_submission_count().get(),
                 min_submissions: self.min_submission_count().get(),
                 timeout: self.timeout().get(),
                 payment_amount: self.payment_amount().get(),
+                payment_token: self.token_id().get(),
+                bonus_submissions: self.bonus_submissions().get(),
+                bonus_amount: self.bonus_amount().get(),
+                values_count: self.values_count().get(),
+                value_offset: self.negative_value_bound().get(),
             },
         );
+        self.new_round_event(*round_id, &self.blockchain().get_caller(), started_at);
+
+        let retention = self.round_retention().get();
+        if retention > 0 && *round_id > retention {
+            self.prune_rounds_internal(round_id - retention - 1, MAX_AUTO_PRUNED_ROUNDS);
+        }
         Ok(())
     }
 
-    fn oracle_initialize_new_round(&self, round_id: u64) -> SCResult<()> {
+    fn oracle_initialize_new_round(&self, round_id: u64, oracle: &Address) -> SCResult<()> {
         if !self.new_round(&round_id) {
             return Ok(());
         }
-        let oracle = self.blockchain().get_caller();
-        let mut oracle_status = self.get_oracle_status_result(&oracle)?;
-        let restart_delay = self.restart_delay().get();
+        let mut oracle_status = self.get_oracle_status_result(oracle)?;
+        let restart_delay = self.oracle_restart_delay(&oracle_status);
         if round_id <= oracle_status.last_started_round + restart_delay
             && oracle_status.last_started_round != 0
         {
             return Ok(());
         }
+        self.require_below_unanswered_rounds_limit()?;
 
         self.initialize_new_round(&round_id)?;
 
         oracle_status.last_started_round = round_id;
-        self.oracles().insert(oracle, oracle_status);
+        self.oracles().insert(oracle.clone(), oracle_status);
         Ok(())
     }
 
@@ -502,198 +3742,891 @@ pub trait Aggregator {
             return Ok(());
         }
 
-        require!(
-            round_id > requester.last_started_round + requester.delay
-                || requester.last_started_round == 0,
-            "must delay requests"
+        require!(
+            round_id > requester.last_started_round + requester.delay
+                || requester.last_started_round == 0,
+            "must delay requests"
+        );
+
+        self.initialize_new_round(&round_id)?;
+
+        requester.last_started_round = round_id;
+        self.requesters().insert(requester_address, requester);
+        Ok(())
+    }
+
+    fn update_timed_out_round_info(&self, round_id: u64) -> SCResult<()> {
+        if !self.timed_out(&round_id)? {
+            return Ok(());
+        }
+        let mut round = self.get_round(&round_id)?;
+        // round 0 always counts as timed out, so the policy only applies to later rounds
+        match self.get_timeout_policy() {
+            TimeoutPolicy::MarkStale if round_id > 0 => self.mark_round_stale(&mut round),
+            TimeoutPolicy::Halt if round_id > 0 => {
+                self.mark_round_stale(&mut round);
+                self.halted().set(&true);
+                self.feed_halted_event(round_id);
+            }
+            _ => self.carry_previous_answer(&mut round)?,
+        }
+        round.updated_at = self.blockchain().get_block_timestamp();
+        self.rounds().insert(round_id, round);
+        self.remove_round_details(round_id);
+        Ok(())
+    }
+
+    /// Closes the round whatever its state, carrying the previous answer over if it was
+    /// not answered.
+    fn force_close_round(&self, round_id: u64) -> SCResult<()> {
+        let mut round = self.get_round(&round_id)?;
+        if round.answered_in_round != round_id {
+            self.carry_previous_answer(&mut round)?;
+            round.updated_at = self.blockchain().get_block_timestamp();
+            self.rounds().insert(round_id, round);
+        }
+        self.remove_round_details(round_id);
+        Ok(())
+    }
+
+    /// Leaves the round without an answer and flags it as stale.
+    fn mark_round_stale(&self, round: &mut Round<Self::BigUint>) {
+        self.clear_answer(round);
+        self.stale_rounds().insert(round.round_id);
+        self.round_stale_event(round.round_id);
+    }
+
+    /// Sets the answer of the round to the answer of the previous round.
+    fn carry_previous_answer(&self, round: &mut Round<Self::BigUint>) -> SCResult<()> {
+        round.fresh = false;
+        if let Some(prev_id) = round.round_id.checked_sub(1) {
+            let prev_round = self.get_round(&prev_id)?;
+            round.answer = prev_round.answer;
+            round.answered_in_round = prev_round.answered_in_round;
+            round.dispersion = prev_round.dispersion;
+            round.min_observation_age = prev_round.min_observation_age;
+            round.median_observation_age = prev_round.median_observation_age;
+            round.max_observation_age = prev_round.max_observation_age;
+        } else {
+            self.clear_answer(round);
+        }
+        Ok(())
+    }
+
+    fn clear_answer(&self, round: &mut Round<Self::BigUint>) {
+        round.fresh = false;
+        round.answer = None;
+        round.answered_in_round = 0;
+        round.dispersion = Vec::new();
+        round.min_observation_age = 0;
+        round.median_observation_age = 0;
+        round.max_observation_age = 0;
+    }
+
+    fn eligible_for_specific_round(
+        &self,
+        oracle: &Address,
+        queried_round_id: &u64,
+    ) -> SCResult<bool> {
+        if self
+            .rounds()
+            .get(queried_round_id)
+            .map_or_else(|| false, |round| round.started_at > 0)
+        {
+            Ok(self.accepting_submissions(&queried_round_id)?
+                && self.validate_oracle_round(oracle, queried_round_id).is_ok())
+        } else {
+            Ok(self.delayed(oracle, queried_round_id)?
+                && self.validate_oracle_round(oracle, queried_round_id).is_ok())
+        }
+    }
+
+    fn oracle_round_state_suggest_round(
+        &self,
+        oracle: &Address,
+    ) -> SCResult<OracleRoundState<Self::BigUint>> {
+        let oracle_status = self.get_oracle_status_result(oracle)?;
+
+        let reporting_round_id = self.reporting_round_id().get();
+        let should_supersede = oracle_status.last_reported_round == reporting_round_id
+            || !self.accepting_submissions(&reporting_round_id)?;
+        // Instead of nudging oracles to submit to the next round, the inclusion of
+        // the should_supersede bool in the if condition pushes them towards
+        // submitting in a currently open round.
+        let mut eligible_to_submit: bool;
+        let round: Round<Self::BigUint>;
+        let round_id: u64;
+        let payment_token: TokenIdentifier;
+        let payment_amount: Self::BigUint;
+        if self.supersedable(&reporting_round_id)? && should_supersede {
+            round_id = reporting_round_id + 1;
+            round = self.get_round(&round_id)?;
+
+            payment_token = self.token_id().get();
+            payment_amount = self.payment_amount().get();
+            eligible_to_submit = self.delayed(&oracle, &round_id)?;
+        } else {
+            round_id = reporting_round_id;
+            round = self.get_round(&round_id)?;
+
+            let round_details = self.get_round_details(&round_id)?;
+            payment_token = round_details.payment_token;
+            payment_amount = round_details.payment_amount;
+            eligible_to_submit = self.accepting_submissions(&round_id)?;
+        }
+
+        if self.validate_oracle_round(&oracle, &round_id).is_err() {
+            eligible_to_submit = false;
+        }
+
+        let recorded_funds = self.get_recorded_funds(&payment_token);
+        let round_details = self.get_round_details(&round_id)?;
+
+        Ok(OracleRoundState {
+            eligible_to_submit,
+            round_id,
+            latest_submission: oracle_status.latest_submission,
+            started_at: round.started_at,
+            timeout: round_details.timeout,
+            available_funds: recorded_funds.available,
+            oracle_count: self.oracle_count(),
+            payment_amount: self.oracle_payment(oracle, payment_amount),
+        })
+    }
+
+    fn update_round_answer(&self, round_id: u64) -> SCResult<()> {
+        let details = self.get_round_details(&round_id)?;
+        if self.submission_count(round_id) < details.min_submissions {
+            return Ok(());
+        }
+
+        let strategy = self.get_aggregation_strategy();
+        let trim_percent = self.get_trim_percent();
+        let mut answer = Submission::from_values(Vec::with_capacity(details.values_count));
+        let mut dispersion = Vec::with_capacity(details.values_count);
+        for value_index in 0..details.values_count {
+            let sorted = self.sorted_submission_values(round_id, value_index).get();
+            dispersion.push(median::calculate_sorted_spread(&sorted));
+            match median::calculate_sorted(&sorted, strategy, trim_percent)? {
+                Some(shifted) => {
+                    let (value, negative) =
+                        median::unshift_signed(&shifted, &details.value_offset);
+                    answer.values.push(value);
+                    answer.negative.push(negative);
+                }
+                None => return sc_error!("no submissions for given round id"),
+            }
+        }
+        answer.normalize_signs();
+        if deviation::dispersion_exceeds_band(&answer, &dispersion, self.max_dispersion().get()) {
+            return self.withhold_disputed_answer(round_id, &answer, &dispersion);
+        }
+        self.disputed_rounds().remove(&round_id);
+        let new_answer = Some(answer);
+
+        self.check_deviation(round_id, &new_answer);
+        let mut round = self.get_round(&round_id)?;
+        round.answer = new_answer.clone();
+        round.updated_at = self.blockchain().get_block_timestamp();
+        round.answered_in_round = round_id;
+        round.fresh = true;
+        round.dispersion = dispersion;
+        let timestamps: Vec<u64> = self
+            .round_submissions(round_id)
+            .load_as_vec()
+            .into_iter()
+            .map(|submission| submission.timestamp)
+            .collect();
+        let (min_age, median_age, max_age) =
+            median::observation_ages(&timestamps, round.updated_at);
+        round.min_observation_age = min_age;
+        round.median_observation_age = median_age;
+        round.max_observation_age = max_age;
+        self.answer_updated_event(round_id, round.updated_at, &round.answer);
+        self.rounds().insert(round_id, round);
+        self.latest_round_id().set(&round_id);
+        self.release_escrowed_payments(round_id);
+        self.notify_subscribers(round_id, &new_answer);
+        Ok(())
+    }
+
+    /// Marks the round as disputed. A round which was not answered yet carries the answer
+    /// of the previous round, while an answered round keeps its answer.
+    fn withhold_disputed_answer(
+        &self,
+        round_id: u64,
+        answer: &Submission<Self::BigUint>,
+        dispersion: &[Self::BigUint],
+    ) -> SCResult<()> {
+        let mut round = self.get_round(&round_id)?;
+        if round.answered_in_round != round_id {
+            self.carry_previous_answer(&mut round)?;
+            round.updated_at = self.blockchain().get_block_timestamp();
+            self.rounds().insert(round_id, round);
+        }
+        self.disputed_rounds().insert(round_id);
+        self.round_disputed_event(round_id, answer, dispersion);
+        Ok(())
+    }
+
+    /// Registers the calling contract to be notified of every new answer: `callback` is
+    /// called with the round id followed by the answer values, with a limit of `gas_limit`.
+    /// The paid EGLD is added to the subscriber's balance, which prepays the notifications.
+    /// Subscribing again updates the callback and gas limit and tops up the balance.
+    #[endpoint]
+    #[payable("EGLD")]
+    fn subscribe(
+        &self,
+        callback: BoxedBytes,
+        gas_limit: u64,
+        #[payment] payment: Self::BigUint,
+    ) -> SCResult<()> {
+        let caller = self.blockchain().get_caller();
+        require!(
+            self.blockchain().is_smart_contract(&caller),
+            "only contracts can subscribe"
+        );
+        require!(!callback.is_empty(), "empty callback");
+        require!(
+            gas_limit > 0 && gas_limit <= MAX_NOTIFICATION_GAS_LIMIT,
+            "invalid notification gas limit"
+        );
+        let balance = match self.subscribers().get(&caller) {
+            Some(subscriber) => subscriber.balance + payment,
+            None => {
+                require!(
+                    self.subscribers().len() < MAX_SUBSCRIBERS,
+                    "too many subscribers"
+                );
+                payment
+            }
+        };
+        self.subscribers().insert(
+            caller,
+            Subscriber {
+                callback,
+                gas_limit,
+                balance,
+            },
+        );
+        Ok(())
+    }
+
+    /// Unsubscribes the calling contract and refunds its remaining balance.
+    #[endpoint]
+    fn unsubscribe(&self) -> SCResult<()> {
+        let caller = self.blockchain().get_caller();
+        self.remove_subscriber_internal(&caller)
+    }
+
+    #[endpoint(removeSubscriber)]
+    fn remove_subscriber(&self, subscriber: Address) -> SCResult<()> {
+        only_governance!(self, b"removeSubscriber", subscriber);
+        self.remove_subscriber_internal(&subscriber)
+    }
+
+    fn remove_subscriber_internal(&self, address: &Address) -> SCResult<()> {
+        match self.subscribers().remove(address) {
+            Some(subscriber) => {
+                if subscriber.balance > Self::BigUint::zero() {
+                    self.send().direct_egld(address, &subscriber.balance, b"subscription refund");
+                }
+                Ok(())
+            }
+            None => sc_error!("not subscribed"),
+        }
+    }
+
+    #[view(getSubscribers)]
+    fn get_subscribers(
+        &self,
+    ) -> MultiResultVec<MultiArg4<Address, BoxedBytes, u64, Self::BigUint>> {
+        self.subscribers()
+            .iter()
+            .map(|(address, subscriber)| {
+                MultiArg4::from((
+                    address,
+                    subscriber.callback,
+                    subscriber.gas_limit,
+                    subscriber.balance,
+                ))
+            })
+            .collect()
+    }
+
+    #[endpoint(setNotificationGasPrice)]
+    fn set_notification_gas_price(&self, gas_price: Self::BigUint) -> SCResult<()> {
+        only_governance!(self, b"setNotificationGasPrice", gas_price);
+        self.notification_gas_price().set(&gas_price);
+        Ok(())
+    }
+
+    #[view(getNotificationGasPrice)]
+    fn get_notification_gas_price(&self) -> Self::BigUint {
+        if self.notification_gas_price().is_empty() {
+            Self::BigUint::from(DEFAULT_NOTIFICATION_GAS_PRICE)
+        } else {
+            self.notification_gas_price().get()
+        }
+    }
+
+    #[view(getNotificationRewards)]
+    fn get_notification_rewards(&self, caller: Address) -> Self::BigUint {
+        self.notification_rewards(&caller).get()
+    }
+
+    /// Pays the caller the fees of the notifications sent in its transactions.
+    #[endpoint(claimNotificationRewards)]
+    fn claim_notification_rewards(&self) -> SCResult<()> {
+        let caller = self.blockchain().get_caller();
+        let rewards = self.notification_rewards(&caller).get();
+        require!(rewards > Self::BigUint::zero(), "no notification rewards");
+        self.notification_rewards(&caller).clear();
+        self.send().direct_egld(&caller, &rewards, b"notification rewards");
+        Ok(())
+    }
+
+    /// Fire-and-forget calls limited to each subscriber's gas limit, so a failing or
+    /// expensive subscriber can never block the round from being answered. Every
+    /// notification is charged to the subscriber's balance, whether the callback
+    /// succeeds or not, and credited to the caller which pays for its gas; subscribers
+    /// whose balance can't cover a notification, or which can't be notified with the
+    /// gas left, are skipped.
+    fn notify_subscribers(&self, round_id: u64, answer: &Option<Submission<Self::BigUint>>) {
+        let answer = match answer {
+            Some(answer) => answer,
+            None => return,
+        };
+        let arg_buffer = self.answer_arguments(round_id, answer);
+        let gas_price = self.get_notification_gas_price();
+        let mut fees = Self::BigUint::zero();
+        let addresses: Vec<Address> = self.subscribers().keys().collect();
+        for address in addresses.iter() {
+            let mut subscriber = match self.subscribers().get(address) {
+                Some(subscriber) => subscriber,
+                None => continue,
+            };
+            let fee = gas_price.clone() * Self::BigUint::from(subscriber.gas_limit);
+            if subscriber.balance < fee
+                || self.blockchain().get_gas_left() <= subscriber.gas_limit
+            {
+                self.subscriber_skipped_event(round_id, address);
+                continue;
+            }
+            subscriber.balance -= &fee;
+            fees += &fee;
+            let gas_limit = subscriber.gas_limit;
+            let callback = subscriber.callback.clone();
+            self.subscribers().insert(address.clone(), subscriber);
+            let _ = self.send().direct_egld_execute(
+                address,
+                &Self::BigUint::zero(),
+                gas_limit,
+                callback.as_slice(),
+                &arg_buffer,
+            );
+        }
+        if fees > Self::BigUint::zero() {
+            let caller = self.blockchain().get_caller();
+            self.notification_rewards(&caller).update(|rewards| *rewards += fees);
+        }
+    }
+
+    /// The arguments passed to the consumers of an answer: the round id followed by the
+    /// answer values.
+    fn answer_arguments(&self, round_id: u64, answer: &Submission<Self::BigUint>) -> ArgBuffer {
+        let mut arg_buffer = ArgBuffer::new();
+        arg_buffer.push_argument_bytes(&round_id.to_be_bytes());
+        for value in answer.values.iter() {
+            arg_buffer.push_argument_bytes(value.to_bytes_be().as_slice());
+        }
+        arg_buffer
+    }
+
+    fn check_deviation(&self, round_id: u64, new_answer: &Option<Submission<Self::BigUint>>) {
+        let threshold = self.flagging_threshold().get();
+        if threshold == 0 || self.flags_address().is_empty() {
+            return;
+        }
+        let previous_answer = self
+            .rounds()
+            .get(&self.latest_round_id().get())
+            .and_then(|round| round.answer);
+        if let (Some(previous), Some(current)) = (previous_answer, new_answer) {
+            if deviation::submission_exceeds_threshold(&previous, current, threshold) {
+                self.raise_deviation_flag(round_id);
+            }
+        }
+    }
+
+    /// Fire-and-forget call to the flags contract, so a failure there can never block
+    /// the round from being answered.
+    fn raise_deviation_flag(&self, round_id: u64) {
+        let flags_address = self.flags_address().get();
+        let mut arg_buffer = ArgBuffer::new();
+        arg_buffer.push_argument_bytes(self.blockchain().get_sc_address().as_bytes());
+        let _ = self.send().direct_egld_execute(
+            &flags_address,
+            &Self::BigUint::zero(),
+            FLAG_RAISING_GAS_LIMIT,
+            b"raiseFlag",
+            &arg_buffer,
         );
+        self.deviation_flag_raised_event(round_id, &flags_address);
+    }
 
-        self.initialize_new_round(&round_id)?;
+    /// `position` is the 1-based order in which the oracle submitted in the round; the
+    /// first `bonus_submissions` oracles are paid the early submitter bonus on top.
+    fn pay_oracle(&self, round_id: u64, oracle: &Address, position: u64) -> SCResult<()> {
+        let round_details = self.get_round_details(&round_id)?;
+        let token_id = round_details.payment_token;
 
-        requester.last_started_round = round_id;
-        self.requesters().insert(requester_address, requester);
-        Ok(())
-    }
+        let mut payment = self.oracle_payment(oracle, round_details.payment_amount);
+        if position <= round_details.bonus_submissions {
+            payment += &round_details.bonus_amount;
+        }
 
-    fn update_timed_out_round_info(&self, round_id: u64) -> SCResult<()> {
-        if !self.timed_out(&round_id)? {
+        let answered = self.get_round(&round_id)?.answered_in_round == round_id;
+        if self.escrow_payments().get() && !answered {
+            let (amount, owed) = self.allocate_payment(oracle, &token_id, payment);
+            self.escrowed_payments(round_id).push(&EscrowedPayment {
+                oracle: oracle.clone(),
+                token_id,
+                amount,
+                owed,
+            });
             return Ok(());
         }
-        let mut round = self.get_round(&round_id)?;
-        if let Some(prev_id) = round_id.checked_sub(1) {
-            let prev_round = self.get_round(&prev_id)?;
-            round.answer = prev_round.answer;
-            round.answered_in_round = prev_round.answered_in_round;
-        } else {
-            round.answer = None;
-            round.answered_in_round = 0;
+        if self.batched_payouts().get() {
+            self.accrue_earnings(oracle, &token_id, payment);
+            return Ok(());
         }
-        round.updated_at = self.blockchain().get_block_timestamp();
-        self.rounds().insert(round_id, round);
-        self.details().remove(&round_id);
+        self.credit_oracle(oracle, &token_id, payment);
         Ok(())
     }
 
-    fn eligible_for_specific_round(
+    /// Allocates the payment from the available funds. An underfunded feed keeps accepting
+    /// submissions, so the part of the payment which the funds cannot cover is returned
+    /// separately, to be owed to the oracle.
+    fn allocate_payment(
         &self,
         oracle: &Address,
-        queried_round_id: &u64,
-    ) -> SCResult<bool> {
-        if self
-            .rounds()
-            .get(queried_round_id)
-            .map_or_else(|| false, |round| round.started_at > 0)
-        {
-            Ok(self.accepting_submissions(&queried_round_id)?
-                && self.validate_oracle_round(oracle, queried_round_id).is_ok())
+        token_id: &TokenIdentifier,
+        mut payment: Self::BigUint,
+    ) -> (Self::BigUint, Self::BigUint) {
+        let available = self.recorded_funds(token_id).get().available;
+        let owed = if payment > available {
+            let owed = payment - available.clone();
+            payment = available;
+            self.payment_owed_event(oracle, token_id, &owed);
+            owed
         } else {
-            Ok(self.delayed(oracle, queried_round_id)?
-                && self.validate_oracle_round(oracle, queried_round_id).is_ok())
-        }
+            Self::BigUint::zero()
+        };
+        self.recorded_funds(token_id).update(|recorded_funds| {
+            recorded_funds.available -= &payment;
+            recorded_funds.allocated += &payment;
+        });
+        (payment, owed)
     }
 
-    fn oracle_round_state_suggest_round(
+    fn credit_oracle(&self, oracle: &Address, token_id: &TokenIdentifier, payment: Self::BigUint) {
+        let (amount, owed) = self.allocate_payment(oracle, token_id, payment);
+        self.oracle_withdrawable(oracle, token_id)
+            .update(|withdrawable| *withdrawable += &amount);
+        self.add_owed_payment(oracle, token_id, owed);
+    }
+
+    /// Adds the payment to the earnings of the oracle in the current epoch, settling its
+    /// earnings of a previous epoch (or in another token) first.
+    fn accrue_earnings(
         &self,
         oracle: &Address,
-    ) -> SCResult<OracleRoundState<Self::BigUint>> {
-        let oracle_status = self.get_oracle_status_result(oracle)?;
+        token_id: &TokenIdentifier,
+        payment: Self::BigUint,
+    ) {
+        let epoch = self.blockchain().get_block_epoch();
+        if !self.pending_earnings(oracle).is_empty() {
+            let mut earnings = self.pending_earnings(oracle).get();
+            if earnings.epoch == epoch && &earnings.token_id == token_id {
+                earnings.amount += &payment;
+                self.pending_earnings(oracle).set(&earnings);
+                return;
+            }
+            self.settle_pending_earnings(oracle);
+        }
+        self.pending_earnings(oracle).set(&PendingEarnings {
+            epoch,
+            token_id: token_id.clone(),
+            amount: payment,
+        });
+    }
 
-        let reporting_round_id = self.reporting_round_id().get();
-        let should_supersede = oracle_status.last_reported_round == reporting_round_id
-            || !self.accepting_submissions(&reporting_round_id)?;
-        // Instead of nudging oracles to submit to the next round, the inclusion of
-        // the should_supersede bool in the if condition pushes them towards
-        // submitting in a currently open round.
-        let mut eligible_to_submit: bool;
-        let round: Round<Self::BigUint>;
-        let round_id: u64;
-        let payment_amount: Self::BigUint;
-        if self.supersedable(&reporting_round_id)? && should_supersede {
-            round_id = reporting_round_id + 1;
-            round = self.get_round(&round_id)?;
+    fn settle_pending_earnings(&self, oracle: &Address) {
+        if self.pending_earnings(oracle).is_empty() {
+            return;
+        }
+        let earnings = self.pending_earnings(oracle).get();
+        self.pending_earnings(oracle).clear();
+        self.credit_oracle(oracle, &earnings.token_id, earnings.amount);
+    }
 
-            payment_amount = self.payment_amount().get();
-            eligible_to_submit = self.delayed(&oracle, &round_id)?;
-        } else {
-            round_id = reporting_round_id;
-            round = self.get_round(&round_id)?;
+    /// Settles the pending earnings of all the oracles, before the available funds are used
+    /// for anything else than paying the oracles.
+    fn settle_all_pending_earnings(&self) {
+        for oracle in self.oracles().keys() {
+            self.settle_pending_earnings(&oracle);
+        }
+    }
 
-            let round_details = self.get_round_details(&round_id)?;
-            payment_amount = round_details.payment_amount;
-            eligible_to_submit = self.accepting_submissions(&round_id)?;
+    fn release_escrowed_payments(&self, round_id: u64) {
+        let mut escrowed_payments = self.escrowed_payments(round_id);
+        for payment in escrowed_payments.load_as_vec().into_iter() {
+            self.oracle_withdrawable(&payment.oracle, &payment.token_id)
+                .update(|withdrawable| *withdrawable += &payment.amount);
+            self.add_owed_payment(&payment.oracle, &payment.token_id, payment.owed);
         }
+        escrowed_payments.clear();
+    }
 
-        if self.validate_oracle_round(&oracle, &round_id).is_err() {
-            eligible_to_submit = false;
+    /// Payments are released as soon as their round is answered, so any payment still
+    /// held is refunded, and the part owed is dropped.
+    fn refund_escrowed_payments(&self, round_id: u64) {
+        let mut escrowed_payments = self.escrowed_payments(round_id);
+        for payment in escrowed_payments.load_as_vec().into_iter() {
+            self.recorded_funds(&payment.token_id).update(|recorded_funds| {
+                recorded_funds.allocated -= &payment.amount;
+                recorded_funds.available += &payment.amount;
+            });
         }
+        escrowed_payments.clear();
+    }
+
+    /// Returns the number of submissions in the round, including this one.
+    fn record_submission(
+        &self,
+        mut submission: Submission<Self::BigUint>,
+        round_id: u64,
+        oracle: &Address,
+    ) -> SCResult<u64> {
+        require!(
+            self.accepting_submissions(&round_id)?,
+            "round not accepting submissions"
+        );
+        require!(
+            submission.negative.is_empty()
+                || submission.negative.len() == submission.values.len(),
+            "incorrect number of signs in submission"
+        );
+        submission.normalize_signs();
+
+        self.check_submission_deviation(&submission, round_id, oracle)?;
 
-        let recorded_funds = self.recorded_funds().get();
         let round_details = self.get_round_details(&round_id)?;
+        require!(
+            submission.values.len() == round_details.values_count,
+            "incorrect number of values in submission"
+        );
+        self.validate_submission_limits(&submission, &round_details.value_offset)?;
+        let mut oracle_status = self.get_oracle_status_result(oracle)?;
+        let weight = self.oracle_weight(oracle);
+        for (value_index, value) in submission.values.iter().enumerate() {
+            let shifted = median::shift_signed(
+                value,
+                submission.is_negative(value_index),
+                &round_details.value_offset,
+            );
+            let mut sorted = self.sorted_submission_values(round_id, value_index).get();
+            median::insert_sorted(&mut sorted, shifted, weight);
+            self.sorted_submission_values(round_id, value_index).set(&sorted);
+        }
+        let timestamp = self.blockchain().get_block_timestamp();
+        self.round_submissions(round_id).push(&RoundSubmission {
+            oracle: oracle.clone(),
+            submission: submission.clone(),
+            timestamp,
+        });
+        self.oracle_last_submission(oracle).set(&timestamp);
+        oracle_status.last_reported_round = round_id;
+        let latency = timestamp - self.get_round(&round_id)?.started_at;
+        let missed = self.uncounted_missed_rounds(oracle, &oracle_status, round_id);
+        self.update_oracle_stats(oracle, |stats| {
+            stats.rounds_eligible += missed + 1;
+            stats.rounds_missed += missed;
+            stats.rounds_submitted += 1;
+            stats.total_submission_latency += latency;
+        });
+        self.oracle_liveness_round(oracle).set(&round_id);
+        self.submission_received_event(round_id, oracle, &submission);
+        oracle_status.latest_submission = Some(submission);
+        let position = self.submission_count(round_id);
+        self.oracles().insert(oracle.clone(), oracle_status);
+        Ok(position)
+    }
 
-        Ok(OracleRoundState {
-            eligible_to_submit,
-            round_id,
-            latest_submission: oracle_status.latest_submission,
-            started_at: round.started_at,
-            timeout: round_details.timeout,
-            available_funds: recorded_funds.available,
-            oracle_count: self.oracle_count(),
-            payment_amount,
-        })
+    /// Oracles have to bond at least `min_stake` of `token_id` before they can be enabled
+    /// and submit. Withdrawing a stake takes `unbonding_period` seconds. A zero stake
+    /// disables the requirement.
+    #[endpoint(setStakeRequirement)]
+    fn set_stake_requirement(
+        &self,
+        token_id: TokenIdentifier,
+        min_stake: Self::BigUint,
+        unbonding_period: u64,
+    ) -> SCResult<()> {
+        only_governance!(
+            self,
+            b"setStakeRequirement",
+            token_id,
+            min_stake,
+            unbonding_period,
+        );
+        require!(
+            self.stake_token_id().is_empty() || self.stake_token_id().get() == token_id,
+            "stake token cannot be changed"
+        );
+        self.stake_token_id().set(&token_id);
+        self.min_stake().set(&min_stake);
+        self.unbonding_period().set(&unbonding_period);
+        Ok(())
     }
 
-    fn update_round_answer(&self, round_id: u64) -> SCResult<()> {
-        let details = self.get_round_details(&round_id)?;
-        if (details.submissions.len() as u64) < details.min_submissions {
-            return Ok(());
+    #[view(getStakeRequirement)]
+    fn get_stake_requirement(
+        &self,
+    ) -> OptionalResult<MultiArg3<TokenIdentifier, Self::BigUint, u64>> {
+        if self.stake_token_id().is_empty() {
+            return OptionalResult::None;
         }
+        OptionalResult::Some(MultiArg3::from((
+            self.stake_token_id().get(),
+            self.min_stake().get(),
+            self.unbonding_period().get(),
+        )))
+    }
 
-        match median::calculate_submission_median(details.submissions) {
-            Result::Ok(new_answer) => {
-                let mut round = self.get_round(&round_id)?;
-                round.answer = new_answer;
-                round.updated_at = self.blockchain().get_block_timestamp();
-                round.answered_in_round = round_id;
-                self.rounds().insert(round_id, round);
-                self.latest_round_id().set(&round_id);
-                Ok(())
-            }
-            Result::Err(error_message) => SCResult::Err(error_message.into()),
+    /// Bonds the paid amount for an oracle. Anyone may bond for an oracle, but only the
+    /// oracle or its admin may unbond.
+    #[endpoint(stake)]
+    #[payable("*")]
+    fn stake(
+        &self,
+        oracle: Address,
+        #[payment] payment: Self::BigUint,
+        #[payment_token] token: TokenIdentifier,
+    ) -> SCResult<()> {
+        require!(!self.stake_token_id().is_empty(), "staking not configured");
+        require!(token == self.stake_token_id().get(), "Wrong token type");
+        let mut stake = self.get_oracle_stake(&oracle);
+        stake.bonded += &payment;
+        self.oracle_stakes(&oracle).set(&stake);
+        Ok(())
+    }
+
+    /// Starts unbonding part of the stake, which restarts the unbonding period of any
+    /// amount already unbonding.
+    #[endpoint(unbondStake)]
+    fn unbond_stake(&self, oracle: Address, amount: Self::BigUint) -> SCResult<()> {
+        self.require_oracle_or_admin(&oracle)?;
+        let mut stake = self.get_oracle_stake(&oracle);
+        require!(stake.bonded >= amount, "insufficient bonded stake");
+        stake.bonded -= &amount;
+        stake.unbonding += &amount;
+        stake.unbonding_ends_at =
+            self.blockchain().get_block_timestamp() + self.unbonding_period().get();
+        self.oracle_stakes(&oracle).set(&stake);
+        Ok(())
+    }
+
+    #[endpoint(withdrawStake)]
+    fn withdraw_stake(&self, oracle: Address) -> SCResult<()> {
+        self.require_oracle_or_admin(&oracle)?;
+        let mut stake = self.get_oracle_stake(&oracle);
+        require!(
+            stake.unbonding > Self::BigUint::zero(),
+            "nothing to withdraw"
+        );
+        require!(
+            self.blockchain().get_block_timestamp() >= stake.unbonding_ends_at,
+            "stake still unbonding"
+        );
+        let amount = stake.unbonding.clone();
+        stake.unbonding = Self::BigUint::zero();
+        self.oracle_stakes(&oracle).set(&stake);
+        self.send_funds(
+            &self.stake_token_id().get(),
+            &self.blockchain().get_caller(),
+            &amount,
+            b"stake withdraw",
+        );
+        Ok(())
+    }
+
+    /// Slashes a misbehaving oracle, taking the amount from its bonded stake first and
+    /// then from the stake which is still unbonding.
+    #[endpoint(slashStake)]
+    fn slash_stake(
+        &self,
+        oracle: Address,
+        amount: Self::BigUint,
+        recipient: Address,
+    ) -> SCResult<()> {
+        only_governance!(self, b"slashStake", oracle, amount, recipient);
+        let mut stake = self.get_oracle_stake(&oracle);
+        require!(
+            stake.bonded.clone() + stake.unbonding.clone() >= amount,
+            "insufficient stake"
+        );
+        if stake.bonded >= amount {
+            stake.bonded -= &amount;
+        } else {
+            stake.unbonding -= &(amount.clone() - stake.bonded.clone());
+            stake.bonded = Self::BigUint::zero();
         }
+        self.oracle_stakes(&oracle).set(&stake);
+        self.send_funds(
+            &self.stake_token_id().get(),
+            &recipient,
+            &amount,
+            b"stake slashed",
+        );
+        self.stake_slashed_event(&oracle, &recipient, &amount);
+        Ok(())
     }
 
-    fn subtract_amount_from_deposits(&self, amount: &Self::BigUint) {
-        let mut remaining = amount.clone();
-        let mut final_amounts: Vec<(Address, Self::BigUint)> = Vec::new();
-        for (account, deposit) in self.deposits().iter() {
-            if remaining == Self::BigUint::zero() {
-                break;
-            }
-            if deposit <= remaining {
-                final_amounts.push((account, Self::BigUint::zero()));
-                remaining -= deposit;
-            } else {
-                final_amounts.push((account, deposit - remaining));
-                remaining = Self::BigUint::zero();
+    #[view(getOracleStake)]
+    fn get_oracle_stake_view(&self, oracle: Address) -> OracleStake<Self::BigUint> {
+        self.get_oracle_stake(&oracle)
+    }
+
+    fn get_oracle_stake(&self, oracle: &Address) -> OracleStake<Self::BigUint> {
+        if self.oracle_stakes(oracle).is_empty() {
+            OracleStake {
+                bonded: Self::BigUint::zero(),
+                unbonding: Self::BigUint::zero(),
+                unbonding_ends_at: 0,
             }
-        }
-        for (account, final_amount) in final_amounts.iter() {
-            self.set_deposit(account, final_amount);
+        } else {
+            self.oracle_stakes(oracle).get()
         }
     }
 
-    fn pay_oracle(&self, round_id: u64) -> SCResult<()> {
-        let round_details = self.get_round_details(&round_id)?;
-        let oracle = self.blockchain().get_caller();
-        let mut oracle_status = self.get_oracle_status_result(&oracle)?;
-
-        let payment = round_details.payment_amount;
-        self.recorded_funds().update(|recorded_funds| {
-            recorded_funds.available -= &payment;
-            recorded_funds.allocated += &payment;
-        });
-        self.subtract_amount_from_deposits(&payment);
+    fn is_bonded(&self, oracle: &Address) -> bool {
+        let min_stake = self.min_stake().get();
+        min_stake == Self::BigUint::zero() || self.get_oracle_stake(oracle).bonded >= min_stake
+    }
 
-        oracle_status.withdrawable += &payment;
-        self.oracles().insert(oracle, oracle_status);
-        Ok(())
+    fn require_oracle_or_admin(&self, oracle: &Address) -> SCResult<()> {
+        let caller = self.blockchain().get_caller();
+        if &caller == oracle {
+            return Ok(());
+        }
+        self.require_oracle_admin(oracle)
     }
 
-    fn record_submission(
+    /// The rounds before `round_id` the oracle was eligible for since the last round counted
+    /// for it. The oracle missed all of them, since each of its submissions is counted.
+    fn uncounted_missed_rounds(
         &self,
-        submission: Submission<Self::BigUint>,
+        oracle: &Address,
+        oracle_status: &OracleStatus<Self::BigUint>,
         round_id: u64,
-    ) -> SCResult<()> {
-        require!(
-            self.accepting_submissions(&round_id)?,
-            "round not accepting submissions"
+    ) -> u64 {
+        let first = core::cmp::max(
+            self.oracle_liveness_round(oracle).get() + 1,
+            oracle_status.starting_round,
         );
+        let end = core::cmp::min(round_id, oracle_status.ending_round.saturating_add(1));
+        end.saturating_sub(first)
+    }
 
-        let mut round_details = self.get_round_details(&round_id)?;
-        let oracle = self.blockchain().get_caller();
-        let mut oracle_status = self.get_oracle_status_result(&oracle)?;
-        round_details.submissions.push(submission.clone());
-        oracle_status.last_reported_round = round_id;
-        oracle_status.latest_submission = Some(submission);
-        self.details().insert(round_id, round_details);
-        self.oracles().insert(oracle, oracle_status);
-        Ok(())
+    /// Counts the rounds the oracle missed before the reporting round, so they are not lost
+    /// when it is removed.
+    fn settle_oracle_liveness(&self, oracle: &Address) {
+        let oracle_status = match self.oracles().get(oracle) {
+            Some(oracle_status) => oracle_status,
+            None => return,
+        };
+        let reporting_round_id = self.reporting_round_id().get();
+        let missed = self.uncounted_missed_rounds(oracle, &oracle_status, reporting_round_id);
+        if missed > 0 {
+            self.update_oracle_stats(oracle, |stats| {
+                stats.rounds_eligible += missed;
+                stats.rounds_missed += missed;
+            });
+        }
+        self.oracle_liveness_round(oracle)
+            .set(&reporting_round_id.saturating_sub(1));
+    }
+
+    fn update_oracle_stats<F: FnOnce(&mut OracleStats)>(&self, oracle: &Address, f: F) {
+        let mut stats = self.get_oracle_stats_or_default(oracle);
+        f(&mut stats);
+        self.oracle_stats(oracle).set(&stats);
+    }
+
+    fn get_oracle_stats_or_default(&self, oracle: &Address) -> OracleStats {
+        if self.oracle_stats(oracle).is_empty() {
+            OracleStats::default()
+        } else {
+            self.oracle_stats(oracle).get()
+        }
+    }
+
+    /// Returns the rounds the oracle was eligible for, submitted to and missed, and its
+    /// average submission latency (in seconds since the round started).
+    #[view(getOracleStats)]
+    fn get_oracle_stats(&self, oracle: Address) -> MultiArg4<u64, u64, u64, u64> {
+        let mut stats = self.get_oracle_stats_or_default(&oracle);
+        if let Some(oracle_status) = self.oracles().get(&oracle) {
+            let reporting_round_id = self.reporting_round_id().get();
+            let missed = self.uncounted_missed_rounds(&oracle, &oracle_status, reporting_round_id);
+            stats.rounds_eligible += missed;
+            stats.rounds_missed += missed;
+        }
+        let average_latency = if stats.rounds_submitted == 0 {
+            0
+        } else {
+            stats.total_submission_latency / stats.rounds_submitted
+        };
+        MultiArg4::from((
+            stats.rounds_eligible,
+            stats.rounds_submitted,
+            stats.rounds_missed,
+            average_latency,
+        ))
     }
 
     fn delete_round_details(&self, round_id: u64) {
         if let Some(details) = self.details().get(&round_id) {
-            if (details.submissions.len() as u64) < details.max_submissions {
+            if self.submission_count(round_id) < details.max_submissions {
                 return;
             }
         }
-        self.details().remove(&round_id);
+        self.remove_round_details(round_id);
     }
 
+    fn remove_round_details(&self, round_id: u64) {
+        if let Some(details) = self.details().remove(&round_id) {
+            for value_index in 0..details.values_count {
+                self.sorted_submission_values(round_id, value_index).clear();
+            }
+        }
+        self.refund_escrowed_payments(round_id);
+    }
+
+    fn submission_count(&self, round_id: u64) -> u64 {
+        self.round_submissions(round_id).len() as u64
+    }
+
+    /// Rounds whose details were already deleted (completed or closed) never time out.
     fn timed_out(&self, round_id: &u64) -> SCResult<bool> {
         let round = self.get_round(round_id)?;
         let started_at = round.started_at;
-        let details = self.get_round_details(round_id)?;
-        let round_timeout = details.timeout;
+        let round_timeout = self
+            .details()
+            .get(round_id)
+            .map_or_else(|| 0, |details| details.timeout);
         Ok(round_id == &0
             || (started_at > 0
                 && round_timeout > 0
@@ -719,16 +4652,54 @@ pub trait Aggregator {
 
     #[view(requiredReserve)]
     fn required_reserve(&self, payment: &Self::BigUint) -> Self::BigUint {
-        payment * &Self::BigUint::from(self.oracle_count()) * Self::BigUint::from(RESERVE_ROUNDS)
+        self.required_reserve_with_bonus(
+            payment,
+            self.bonus_submissions().get(),
+            &self.bonus_amount().get(),
+        )
+    }
+
+    fn required_reserve_with_bonus(
+        &self,
+        payment: &Self::BigUint,
+        bonus_submissions: u64,
+        bonus_amount: &Self::BigUint,
+    ) -> Self::BigUint {
+        let oracle_count = self.oracle_count();
+        let overrides: Vec<Self::BigUint> = self
+            .oracle_payment_overrides()
+            .iter()
+            .filter(|(oracle, _)| self.oracle_enabled(oracle))
+            .map(|(_, payment_override)| payment_override)
+            .collect();
+        let bonus_count = core::cmp::min(bonus_submissions, oracle_count);
+        let mut per_round = payment * &Self::BigUint::from(oracle_count - overrides.len() as u64)
+            + bonus_amount * &Self::BigUint::from(bonus_count);
+        for payment_override in overrides.iter() {
+            per_round += payment_override;
+        }
+        per_round * Self::BigUint::from(self.get_reserve_rounds())
     }
 
     fn add_oracle(&self, oracle: &Address, admin: &Address) -> SCResult<()> {
         require!(!self.oracle_enabled(oracle), "oracle already enabled");
+        require!(self.is_bonded(oracle), "oracle not bonded");
+
+        // a retired oracle keeps its admin, and with it its unclaimed payments
+        let payout_address = match self.get_oracle_status_option(oracle) {
+            Some(oracle_status) => {
+                require!(
+                    &oracle_status.admin == admin,
+                    "owner cannot overwrite admin"
+                );
+                oracle_status.payout_address
+            }
+            None => None,
+        };
 
         self.oracles().insert(
             oracle.clone(),
             OracleStatus {
-                withdrawable: Self::BigUint::zero(),
                 starting_round: self.get_starting_round(oracle),
                 ending_round: ROUND_MAX,
                 last_reported_round: 0,
@@ -736,8 +4707,11 @@ pub trait Aggregator {
                 latest_submission: None,
                 admin: admin.clone(),
                 pending_admin: None,
+                payout_address,
+                restart_delay: None,
             },
         );
+        self.oracle_permissions_updated_event(oracle, true);
         Ok(())
     }
 
@@ -746,6 +4720,7 @@ pub trait Aggregator {
         let reporting_round_id = self.reporting_round_id().get();
 
         require!(oracle_status.starting_round != 0, "not enabled oracle");
+        require!(self.is_bonded(oracle), "oracle not bonded");
         require!(
             oracle_status.starting_round <= *round_id,
             "not yet enabled oracle"
@@ -768,9 +4743,41 @@ pub trait Aggregator {
             *round_id == 1 || self.supersedable(&(*round_id - 1))?,
             "previous round not supersedable"
         );
+        self.require_committee_member(oracle)?;
+        let min_interval = self.min_submission_interval().get();
+        if min_interval > 0 {
+            let last_submission = self.oracle_last_submission(oracle).get();
+            require!(
+                last_submission == 0
+                    || self.blockchain().get_block_timestamp() >= last_submission + min_interval,
+                "oracle submitting too frequently"
+            );
+        }
         Ok(())
     }
 
+    /// Whether a new round can be started after the given one, because it was answered or
+    /// it timed out.
+    #[view(supersedable)]
+    fn supersedable_view(&self, round_id: u64) -> SCResult<bool> {
+        self.supersedable(&round_id)
+    }
+
+    #[view(timedOut)]
+    fn timed_out_view(&self, round_id: u64) -> SCResult<bool> {
+        self.timed_out(&round_id)
+    }
+
+    /// Whether the round is still open for submissions; completed and closed rounds
+    /// are not.
+    #[view(acceptingSubmissions)]
+    fn accepting_submissions_view(&self, round_id: u64) -> SCResult<bool> {
+        if !self.details().contains_key(&round_id) {
+            return Ok(false);
+        }
+        self.accepting_submissions(&round_id)
+    }
+
     fn supersedable(&self, round_id: &u64) -> SCResult<bool> {
         let round = self.get_round(round_id)?;
         let timed_out = self.timed_out(round_id)?;
@@ -778,7 +4785,8 @@ pub trait Aggregator {
     }
 
     fn oracle_enabled(&self, oracle: &Address) -> bool {
-        self.oracles().contains_key(oracle)
+        self.get_oracle_status_option(oracle)
+            .map_or_else(|| false, |oracle_status| oracle_status.ending_round == ROUND_MAX)
     }
 
     fn accepting_submissions(&self, round_id: &u64) -> SCResult<bool> {
@@ -789,7 +4797,8 @@ pub trait Aggregator {
     fn delayed(&self, oracle: &Address, round_id: &u64) -> SCResult<bool> {
         let oracle_status = self.get_oracle_status_result(oracle)?;
         let last_started = oracle_status.last_started_round;
-        Ok(*round_id > last_started + self.restart_delay().get() || last_started == 0)
+        let restart_delay = self.oracle_restart_delay(&oracle_status);
+        Ok(*round_id > last_started + restart_delay || last_started == 0)
     }
 
     fn new_round(&self, round_id: &u64) -> bool {
@@ -832,4 +4841,121 @@ pub trait Aggregator {
     fn get_oracles(&self) -> MultiResultVec<Address> {
         self.oracles().keys().collect()
     }
+
+    #[event("new_round")]
+    fn new_round_event(
+        &self,
+        #[indexed] round_id: u64,
+        #[indexed] started_by: &Address,
+        started_at: u64,
+    );
+
+    #[event("payment_token_migrated")]
+    fn payment_token_migrated_event(
+        &self,
+        #[indexed] old_token: &TokenIdentifier,
+        #[indexed] new_token: &TokenIdentifier,
+    );
+
+    #[event("new_round_forced")]
+    fn new_round_forced_event(&self, #[indexed] superseded_round_id: u64, new_round_id: u64);
+
+    #[event("submission_received")]
+    fn submission_received_event(
+        &self,
+        #[indexed] round_id: u64,
+        #[indexed] oracle: &Address,
+        submission: &Submission<Self::BigUint>,
+    );
+
+    #[event("submission_deviation")]
+    fn submission_deviation_event(
+        &self,
+        #[indexed] round_id: u64,
+        #[indexed] oracle: &Address,
+        submission: &Submission<Self::BigUint>,
+    );
+
+    #[event("answer_updated")]
+    fn answer_updated_event(
+        &self,
+        #[indexed] round_id: u64,
+        #[indexed] updated_at: u64,
+        answer: &Option<Submission<Self::BigUint>>,
+    );
+
+    #[event("round_disputed")]
+    fn round_disputed_event(
+        &self,
+        #[indexed] round_id: u64,
+        #[indexed] answer: &Submission<Self::BigUint>,
+        dispersion: &[Self::BigUint],
+    );
+
+    #[event("round_stale")]
+    fn round_stale_event(&self, #[indexed] round_id: u64);
+
+    #[event("feed_halted")]
+    fn feed_halted_event(&self, #[indexed] round_id: u64);
+
+    #[event("rounds_resumed")]
+    fn rounds_resumed_event(&self, #[indexed] reporting_round_id: u64);
+
+    #[event("committee_selected")]
+    fn committee_selected_event(&self, #[indexed] epoch: u64, committee_size: u64);
+
+    #[event("payment_owed")]
+    fn payment_owed_event(
+        &self,
+        #[indexed] oracle: &Address,
+        #[indexed] token_id: &TokenIdentifier,
+        owed: &Self::BigUint,
+    );
+
+    #[event("deviation_flag_raised")]
+    fn deviation_flag_raised_event(&self, #[indexed] round_id: u64, flags_address: &Address);
+
+    #[event("subscriber_skipped")]
+    fn subscriber_skipped_event(&self, #[indexed] round_id: u64, #[indexed] subscriber: &Address);
+
+    #[event("oracle_permissions_updated")]
+    fn oracle_permissions_updated_event(&self, #[indexed] oracle: &Address, whitelisted: bool);
+
+    #[event("stake_slashed")]
+    fn stake_slashed_event(
+        &self,
+        #[indexed] oracle: &Address,
+        #[indexed] recipient: &Address,
+        amount: &Self::BigUint,
+    );
+
+    #[event("config_change_proposed")]
+    fn config_change_proposed_event(&self, #[indexed] change: &[u8], execute_after: u64);
+
+    #[event("emergency_withdraw_scheduled")]
+    fn emergency_withdraw_scheduled_event(
+        &self,
+        #[indexed] token_id: &TokenIdentifier,
+        #[indexed] recipient: &Address,
+        #[indexed] execute_after: u64,
+        amount: &Self::BigUint,
+    );
+
+    #[event("emergency_withdraw_executed")]
+    fn emergency_withdraw_executed_event(
+        &self,
+        #[indexed] token_id: &TokenIdentifier,
+        #[indexed] recipient: &Address,
+        amount: &Self::BigUint,
+    );
+
+    #[event("round_details_updated")]
+    fn round_details_updated_event(
+        &self,
+        #[indexed] min_submissions: u64,
+        #[indexed] max_submissions: u64,
+        #[indexed] restart_delay: u64,
+        #[indexed] timeout: u64,
+        payment_amount: &Self::BigUint,
+    );
 }
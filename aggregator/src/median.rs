@@ -1,49 +1,241 @@
 elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
-use crate::aggregator_interface::Submission;
-
-/// Calculates the median for each of the values in a Submission
-pub fn calculate_submission_median<BigUint: BigUintApi>(
-    submissions: Vec<Submission<BigUint>>,
-) -> Result<Option<Submission<BigUint>>, SCError> {
-    if submissions.is_empty() {
-        return Result::Ok(None);
-    }
-    let values_count = submissions.first().unwrap().values.len();
-    let iter = (0..values_count).map(|index| {
-        submissions
-            .iter()
-            .map(|submission| submission.values.iter())
-            .flatten()
-            .skip(index)
-            .step_by(values_count)
-    });
-    let mut new_submission = Submission::<BigUint> { values: Vec::new() };
-    for values in iter {
-        let median = calculate(values.cloned().collect())?.unwrap().clone();
-        new_submission.values.push(median);
-    }
-    Result::Ok(Some(new_submission))
-}
+use crate::aggregator_interface::AggregationStrategy;
 
 /// Returns the sorted middle, or the average of the two middle indexed items if the
 /// vector has an even number of elements.
-pub fn calculate<BigUint: BigUintApi>(mut list: Vec<BigUint>) -> Result<Option<BigUint>, SCError>
+pub fn calculate<BigUint: BigUintApi>(list: Vec<BigUint>) -> Result<Option<BigUint>, SCError>
 where
     BigUint: BigUintApi,
 {
+    let mut refs: Vec<&BigUint> = list.iter().collect();
+    Result::Ok(select_median(&mut refs))
+}
+
+/// Selects the median of the referenced values without sorting them: quickselect moves the
+/// upper middle item into place with every smaller item before it, so the lower middle item
+/// of an even number of items is the largest of those.
+fn select_median<BigUint: BigUintApi>(list: &mut [&BigUint]) -> Option<BigUint> {
+    let len = list.len();
+    if len == 0 {
+        return None;
+    }
+    let (lower, median, _) = list.select_nth_unstable(len / 2);
+    let median = (*median).clone();
+    if len % 2 == 0 {
+        let previous = lower.iter().max()?;
+        Some(((*previous).clone() + median) / 2u64.into())
+    } else {
+        Some(median)
+    }
+}
+
+/// Inserts the (value, weight) pair into a list kept sorted by value.
+pub fn insert_sorted<BigUint: BigUintApi>(
+    list: &mut Vec<(BigUint, u64)>,
+    value: BigUint,
+    weight: u64,
+) {
+    let index = list
+        .binary_search_by(|(item, _)| item.cmp(&value))
+        .unwrap_or_else(|index| index);
+    list.insert(index, (value, weight));
+}
+
+/// Aggregates (value, weight) pairs which are already sorted by value (see `insert_sorted`),
+/// so that none of the strategies needs to sort them again. The values are aggregated
+/// through references, so only the result is cloned. With equal weights the median is
+/// picked directly from the middle of the list.
+pub fn calculate_sorted<BigUint: BigUintApi>(
+    list: &[(BigUint, u64)],
+    strategy: AggregationStrategy,
+    trim_percent: u8,
+) -> Result<Option<BigUint>, SCError> {
+    let first_weight = match list.first() {
+        Some((_, weight)) => *weight,
+        None => return Result::Ok(None),
+    };
+    match strategy {
+        AggregationStrategy::Median => {
+            if first_weight > 0 && list.iter().all(|(_, weight)| *weight == first_weight) {
+                let middle_index = list.len() / 2;
+                let (median, _) = &list[middle_index];
+                if list.len() % 2 == 0 {
+                    let (previous, _) = &list[middle_index - 1];
+                    Result::Ok(Some((previous.clone() + median.clone()) / 2u64.into()))
+                } else {
+                    Result::Ok(Some(median.clone()))
+                }
+            } else {
+                weighted_median_of_sorted(list)
+            }
+        }
+        AggregationStrategy::Mean => Result::Ok(mean_of(list.iter().map(|(value, _)| value))),
+        AggregationStrategy::Mode => Result::Ok(mode_of_sorted(list)),
+        AggregationStrategy::TrimmedMean => {
+            let (start, end) = trimmed_range(list.len(), trim_percent);
+            Result::Ok(mean_of(list[start..end].iter().map(|(value, _)| value)))
+        }
+    }
+}
+
+/// The spread (max - min) of (value, weight) pairs sorted by value.
+pub fn calculate_sorted_spread<BigUint: BigUintApi>(list: &[(BigUint, u64)]) -> BigUint {
+    match (list.first(), list.last()) {
+        (Some((min, _)), Some((max, _))) => max.clone() - min.clone(),
+        _ => BigUint::zero(),
+    }
+}
+
+/// The minimum, median and maximum age at `now` of the observations taken at `timestamps`.
+/// The median of an even number of ages is the mean of the two middle ones, rounded down.
+pub fn observation_ages(timestamps: &[u64], now: u64) -> (u64, u64, u64) {
+    let mut ages: Vec<u64> = timestamps
+        .iter()
+        .map(|timestamp| now.saturating_sub(*timestamp))
+        .collect();
+    if ages.is_empty() {
+        return (0, 0, 0);
+    }
+    ages.sort_unstable();
+    let middle = ages.len() / 2;
+    let median = if ages.len() % 2 == 0 {
+        (ages[middle - 1] + ages[middle]) / 2
+    } else {
+        ages[middle]
+    };
+    (ages[0], median, ages[ages.len() - 1])
+}
+
+/// Maps a signed value to `offset + value`, which keeps the order of signed values as long
+/// as none of them is below `-offset`, so that they can be aggregated as unsigned values.
+pub fn shift_signed<BigUint: BigUintApi>(
+    magnitude: &BigUint,
+    negative: bool,
+    offset: &BigUint,
+) -> BigUint {
+    if negative {
+        offset.clone() - magnitude.clone()
+    } else {
+        offset.clone() + magnitude.clone()
+    }
+}
+
+/// The inverse of `shift_signed`: returns the magnitude and whether the value is negative.
+pub fn unshift_signed<BigUint: BigUintApi>(shifted: &BigUint, offset: &BigUint) -> (BigUint, bool) {
+    if shifted >= offset {
+        (shifted.clone() - offset.clone(), false)
+    } else {
+        (offset.clone() - shifted.clone(), true)
+    }
+}
+
+/// Returns the weighted median of (value, weight) pairs sorted by value: the value at
+/// which the cumulative weight reaches half of the total weight. If the cumulative weight
+/// is exactly half, the average of that value and the next one is returned, so equal
+/// weights yield the same result as `calculate`.
+fn weighted_median_of_sorted<BigUint: BigUintApi>(
+    list: &[(BigUint, u64)],
+) -> Result<Option<BigUint>, SCError> {
+    let total_weight: u64 = list.iter().map(|(_, weight)| weight).sum();
+    if total_weight == 0 {
+        return Result::Ok(None);
+    }
+    let mut cumulative_weight = 0u64;
+    for (index, (value, weight)) in list.iter().enumerate() {
+        cumulative_weight += weight;
+        if cumulative_weight * 2 == total_weight {
+            let (next_value, _) = list
+                .iter()
+                .skip(index + 1)
+                .find(|(_, weight)| *weight > 0)
+                .ok_or("weighted median invalid index")?;
+            return Result::Ok(Some((value.clone() + next_value.clone()) / 2u64.into()));
+        }
+        if cumulative_weight * 2 > total_weight {
+            return Result::Ok(Some(value.clone()));
+        }
+    }
+    Result::Err("weighted median invalid index".into())
+}
+
+/// Basis points of a full quantile range (p100)
+pub const QUANTILE_BP_MAX: u32 = 10_000;
+
+/// Returns the quantile (expressed in basis points) of the items, linearly interpolating
+/// between the two closest ranks, so p50 matches `calculate`.
+pub fn calculate_quantile<BigUint: BigUintApi>(
+    mut list: Vec<BigUint>,
+    quantile_bp: u32,
+) -> Result<Option<BigUint>, SCError> {
+    if quantile_bp > QUANTILE_BP_MAX {
+        return Result::Err("invalid quantile".into());
+    }
     if list.is_empty() {
         return Result::Ok(None);
     }
     list.sort();
-    let len = list.len();
-    let middle_index = len / 2;
-    if len % 2 == 0 {
-        let median1 = list.get(middle_index - 1).ok_or("median1 invalid index")?;
-        let median2 = list.get(middle_index).ok_or("median2 invalid index")?;
-        Result::Ok(Some((median1.clone() + median2.clone()) / 2u64.into()))
-    } else {
-        let median = list.get(middle_index).ok_or("median invalid index")?;
-        Result::Ok(Some(median.clone()))
+    let position = (list.len() as u64 - 1) * quantile_bp as u64;
+    let lower_index = (position / QUANTILE_BP_MAX as u64) as usize;
+    let fraction = position % QUANTILE_BP_MAX as u64;
+    let lower = list.get(lower_index).ok_or("quantile invalid index")?;
+    if fraction == 0 {
+        return Result::Ok(Some(lower.clone()));
+    }
+    let upper = list.get(lower_index + 1).ok_or("quantile invalid index")?;
+    let interpolated =
+        (upper.clone() - lower.clone()) * BigUint::from(fraction) / BigUint::from(QUANTILE_BP_MAX);
+    Result::Ok(Some(lower.clone() + interpolated))
+}
+
+/// Returns the arithmetic mean of the items, rounded down.
+pub fn calculate_mean<BigUint: BigUintApi>(list: Vec<BigUint>) -> Result<Option<BigUint>, SCError> {
+    Result::Ok(mean_of(list.iter()))
+}
+
+fn mean_of<'a, BigUint: BigUintApi + 'a>(
+    values: impl ExactSizeIterator<Item = &'a BigUint>,
+) -> Option<BigUint> {
+    if values.len() == 0 {
+        return None;
+    }
+    let len = BigUint::from(values.len() as u64);
+    let mut sum = BigUint::zero();
+    for value in values {
+        sum += value.clone();
     }
+    Some(sum / len)
+}
+
+/// Returns the most frequent value of (value, weight) pairs sorted by value. If several
+/// values are equally frequent, the smallest of them is returned.
+fn mode_of_sorted<BigUint: BigUintApi>(list: &[(BigUint, u64)]) -> Option<BigUint> {
+    if list.is_empty() {
+        return None;
+    }
+    let mut mode_index = 0;
+    let mut mode_count = 0;
+    let mut run_start = 0;
+    for index in 1..=list.len() {
+        if index == list.len() || list[index].0 != list[run_start].0 {
+            if index - run_start > mode_count {
+                mode_index = run_start;
+                mode_count = index - run_start;
+            }
+            run_start = index;
+        }
+    }
+    Some(list[mode_index].0.clone())
+}
+
+/// The range of the `len` sorted items kept by the trimmed mean, which discards the top and
+/// bottom `trim_percent` percent of them. The number of discarded items is rounded up on
+/// each side, so that any non-zero percentage discards at least one outlier, but at least
+/// one item is always kept.
+fn trimmed_range(len: usize, trim_percent: u8) -> (usize, usize) {
+    let trim_count = core::cmp::min(
+        (len * trim_percent as usize + 99) / 100,
+        len.saturating_sub(1) / 2,
+    );
+    (trim_count, len - trim_count)
 }
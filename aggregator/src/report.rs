@@ -0,0 +1,54 @@
+elrond_wasm::imports!();
+use crate::aggregator_interface::ReportObservation;
+
+/// Builds the message signed by every oracle taking part in a report: the aggregator
+/// address, the round id and then, for each observation, the oracle address followed by
+/// its length-prefixed values and sign flags. Signatures themselves are not part of the
+/// message.
+pub fn report_message<BigUint: BigUintApi>(
+    aggregator: &Address,
+    round_id: u64,
+    observations: &[ReportObservation<BigUint>],
+) -> Vec<u8> {
+    let mut message = Vec::new();
+    message.extend_from_slice(aggregator.as_bytes());
+    message.extend_from_slice(&round_id.to_be_bytes());
+    for observation in observations.iter() {
+        message.extend_from_slice(observation.oracle.as_bytes());
+        push_values(&mut message, &observation.values);
+        push_signs(&mut message, &observation.negative);
+    }
+    message
+}
+
+/// Builds the message signed by an oracle's signing key for a single submission:
+/// the aggregator address, the oracle address, the round id and the length-prefixed values.
+pub fn submission_message<BigUint: BigUintApi>(
+    aggregator: &Address,
+    oracle: &Address,
+    round_id: u64,
+    values: &[BigUint],
+) -> Vec<u8> {
+    let mut message = Vec::new();
+    message.extend_from_slice(aggregator.as_bytes());
+    message.extend_from_slice(oracle.as_bytes());
+    message.extend_from_slice(&round_id.to_be_bytes());
+    push_values(&mut message, values);
+    message
+}
+
+fn push_signs(message: &mut Vec<u8>, negative: &[bool]) {
+    message.extend_from_slice(&(negative.len() as u32).to_be_bytes());
+    for flag in negative.iter() {
+        message.push(*flag as u8);
+    }
+}
+
+fn push_values<BigUint: BigUintApi>(message: &mut Vec<u8>, values: &[BigUint]) {
+    message.extend_from_slice(&(values.len() as u32).to_be_bytes());
+    for value in values.iter() {
+        let value_bytes = value.to_bytes_be();
+        message.extend_from_slice(&(value_bytes.len() as u32).to_be_bytes());
+        message.extend_from_slice(&value_bytes);
+    }
+}
@@ -0,0 +1,30 @@
+elrond_wasm::imports!();
+
+/// Folds random bytes (e.g. a block random seed) into a seed for `select_committee`.
+pub fn seed_from_bytes(bytes: &[u8]) -> u64 {
+    bytes.chunks(8).fold(0u64, |seed, chunk| {
+        let mut word = [0u8; 8];
+        word[..chunk.len()].copy_from_slice(chunk);
+        seed ^ u64::from_be_bytes(word)
+    })
+}
+
+/// Selects `size` members of the pool, or the whole pool if it is not larger. The same
+/// seed always selects the same members, in the same order.
+pub fn select_committee<T: Clone>(pool: &[T], size: usize, seed: u64) -> Vec<T> {
+    let mut candidates = pool.to_vec();
+    if size >= candidates.len() {
+        return candidates;
+    }
+    // xorshift64, which must not be seeded with zero
+    let mut state = if seed == 0 { 0x9e37_79b9_7f4a_7c15 } else { seed };
+    for index in 0..size {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let remaining = (candidates.len() - index) as u64;
+        candidates.swap(index, index + (state % remaining) as usize);
+    }
+    candidates.truncate(size);
+    candidates
+}
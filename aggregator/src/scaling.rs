@@ -0,0 +1,53 @@
+elrond_wasm::imports!();
+
+/// 10 to the power of `exponent`.
+pub fn pow10<BigUint: BigUintApi>(exponent: u8) -> BigUint {
+    let mut result = BigUint::from(1u64);
+    for _ in 0..exponent {
+        result *= BigUint::from(10u64);
+    }
+    result
+}
+
+/// Converts a fixed point value with `from_decimals` decimals to `to_decimals` decimals.
+/// Lowering the precision rounds down.
+pub fn rescale<BigUint: BigUintApi>(
+    value: &BigUint,
+    from_decimals: u8,
+    to_decimals: u8,
+) -> BigUint {
+    if to_decimals >= from_decimals {
+        value.clone() * pow10(to_decimals - from_decimals)
+    } else {
+        value.clone() / pow10(from_decimals - to_decimals)
+    }
+}
+
+/// Crosses the prices of `base` and `quote` in a common asset into the price of `base` in
+/// `quote`, with `target_decimals` decimals, rounded down. None if the quote price is 0.
+pub fn cross_price<BigUint: BigUintApi>(
+    base_price: &BigUint,
+    base_decimals: u8,
+    quote_price: &BigUint,
+    quote_decimals: u8,
+    target_decimals: u8,
+) -> Option<BigUint> {
+    if quote_price == &BigUint::zero() {
+        return None;
+    }
+    let numerator =
+        base_price.clone() * pow10::<BigUint>(quote_decimals) * pow10::<BigUint>(target_decimals);
+    Some(numerator / (quote_price.clone() * pow10::<BigUint>(base_decimals)))
+}
+
+/// Inverts a price with `decimals` decimals (the price of `quote` in `base` from the price
+/// of `base` in `quote`), keeping the same decimals. Rounds to the nearest value, halves
+/// up. None if the price is 0.
+pub fn inverse_price<BigUint: BigUintApi>(price: &BigUint, decimals: u8) -> Option<BigUint> {
+    if price == &BigUint::zero() {
+        return None;
+    }
+    let one = pow10::<BigUint>(decimals);
+    let numerator = one.clone() * one * BigUint::from(2u64) + price.clone();
+    Some(numerator / (price.clone() * BigUint::from(2u64)))
+}
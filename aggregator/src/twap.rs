@@ -0,0 +1,31 @@
+elrond_wasm::imports!();
+
+/// The time-weighted average, over the window from `window_start` to `now`, of the
+/// `(timestamp, value)` observations ordered from the newest. Each value is weighted by
+/// the time it was the latest value inside the window; the part of the window before the
+/// oldest observation is left out. An empty window yields the latest value.
+pub fn time_weighted_average<BigUint: BigUintApi>(
+    observations: &[(u64, BigUint)],
+    window_start: u64,
+    now: u64,
+) -> Option<BigUint> {
+    let mut weighted_sum = BigUint::zero();
+    let mut total_time = 0u64;
+    let mut period_end = now;
+    for (timestamp, value) in observations.iter() {
+        let period_start = core::cmp::max(*timestamp, window_start);
+        if period_end > period_start {
+            let duration = period_end - period_start;
+            weighted_sum += value.clone() * BigUint::from(duration);
+            total_time += duration;
+            period_end = period_start;
+        }
+        if *timestamp <= window_start {
+            break;
+        }
+    }
+    if total_time == 0 {
+        return observations.first().map(|(_, value)| value.clone());
+    }
+    Some(weighted_sum / BigUint::from(total_time))
+}
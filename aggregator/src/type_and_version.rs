@@ -0,0 +1,8 @@
+elrond_wasm::imports!();
+
+/// The value of the `typeAndVersion` view of every contract: its type followed by the
+/// semantic version of its interface, e.g. `Aggregator 1.2.0`, so that deployment tooling
+/// and proxies can check what they are pointed at.
+pub fn type_and_version(contract_type: &str, version: &str) -> BoxedBytes {
+    BoxedBytes::from_concat(&[contract_type.as_bytes(), b" ", version.as_bytes()])
+}
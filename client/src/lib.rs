@@ -1,5 +1,7 @@
 #![no_std]
 
+extern crate aggregator;
+use aggregator::type_and_version;
 mod client_data;
 use client_data::ClientData;
 
@@ -39,6 +41,11 @@ pub trait Client {
         self.set_oracle_address(oracle_address);
     }
 
+    #[view(typeAndVersion)]
+    fn type_and_version(&self) -> BoxedBytes {
+        type_and_version::type_and_version("Client", env!("CARGO_PKG_VERSION"))
+    }
+
     #[endpoint(sendRequest)]
     fn send_request(&self) -> SCResult<AsyncCall<Self::SendApi>> {
         only_owner!(self, "Caller must be owner");
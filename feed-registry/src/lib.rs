@@ -0,0 +1,104 @@
+#![no_std]
+
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+/// A pair of assets, priced as the amount of `quote` for one unit of `base`
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi, Clone)]
+pub struct AssetPair {
+    pub base: BoxedBytes,
+    pub quote: BoxedBytes,
+}
+
+#[elrond_wasm_derive::contract]
+pub trait FeedRegistry {
+    #[init]
+    fn init(&self) {}
+
+    /// Makes `feed` (an aggregator, aggregator proxy or price aggregator) the canonical
+    /// feed of the pair. Every call starts a new version, the previous feeds remaining
+    /// readable through `getFeedAtVersion`.
+    #[endpoint(setFeed)]
+    fn set_feed(&self, base: BoxedBytes, quote: BoxedBytes, feed: Address) -> SCResult<u32> {
+        only_owner!(self, "Only owner may call this function!");
+        require!(!base.is_empty() && !quote.is_empty(), "invalid pair");
+        require!(
+            self.blockchain().is_smart_contract(&feed),
+            "feed must be a smart contract"
+        );
+        let pair = AssetPair { base, quote };
+        let version = self.feed_versions(&pair).push(&feed) as u32;
+        self.pairs().insert(pair.clone());
+        self.feed_set_event(&pair.base, &pair.quote, version, &feed);
+        Ok(version)
+    }
+
+    /// Removes the pair from the registry, so it has no canonical feed anymore. Its
+    /// previous versions remain readable, and setting a feed again starts a new version.
+    #[endpoint(removeFeed)]
+    fn remove_feed(&self, base: BoxedBytes, quote: BoxedBytes) -> SCResult<()> {
+        only_owner!(self, "Only owner may call this function!");
+        let pair = AssetPair { base, quote };
+        require!(self.pairs().remove(&pair), "no feed for pair");
+        self.feed_removed_event(&pair.base, &pair.quote);
+        Ok(())
+    }
+
+    /// The canonical feed of the pair.
+    #[view(getFeed)]
+    fn get_feed(&self, base: BoxedBytes, quote: BoxedBytes) -> SCResult<Address> {
+        let pair = AssetPair { base, quote };
+        require!(self.pairs().contains(&pair), "no feed for pair");
+        let feed_versions = self.feed_versions(&pair);
+        Ok(feed_versions.get(feed_versions.len()))
+    }
+
+    /// The version of the canonical feed of the pair; 0 if it never had a feed.
+    #[view(getFeedVersion)]
+    fn get_feed_version(&self, base: BoxedBytes, quote: BoxedBytes) -> u32 {
+        self.feed_versions(&AssetPair { base, quote }).len() as u32
+    }
+
+    /// The feed of the pair at the given version, starting from 1.
+    #[view(getFeedAtVersion)]
+    fn get_feed_at_version(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        version: u32,
+    ) -> SCResult<Address> {
+        let feed_versions = self.feed_versions(&AssetPair { base, quote });
+        require!(
+            version > 0 && version as usize <= feed_versions.len(),
+            "invalid version"
+        );
+        Ok(feed_versions.get(version as usize))
+    }
+
+    /// The pairs which have a canonical feed.
+    #[view(getPairs)]
+    fn get_pairs(&self) -> MultiResultVec<MultiArg2<BoxedBytes, BoxedBytes>> {
+        self.pairs()
+            .iter()
+            .map(|pair| MultiArg2::from((pair.base, pair.quote)))
+            .collect()
+    }
+
+    #[storage_mapper("pairs")]
+    fn pairs(&self) -> SetMapper<Self::Storage, AssetPair>;
+
+    #[storage_mapper("feed_versions")]
+    fn feed_versions(&self, pair: &AssetPair) -> VecMapper<Self::Storage, Address>;
+
+    #[event("feed_set")]
+    fn feed_set_event(
+        &self,
+        #[indexed] base: &BoxedBytes,
+        #[indexed] quote: &BoxedBytes,
+        #[indexed] version: u32,
+        feed: &Address,
+    );
+
+    #[event("feed_removed")]
+    fn feed_removed_event(&self, #[indexed] base: &BoxedBytes, #[indexed] quote: &BoxedBytes);
+}
@@ -0,0 +1,4 @@
+#![no_std]
+
+pub use elrond_wasm_output::*;
+pub use feed_registry::*;
@@ -0,0 +1,78 @@
+#![no_std]
+
+elrond_wasm::imports!();
+
+#[elrond_wasm_derive::contract]
+pub trait Flags {
+    #[init]
+    fn init(&self) {}
+
+    #[view(getFlag)]
+    fn get_flag(&self, subject: Address) -> bool {
+        self.raised_flags().contains(&subject)
+    }
+
+    #[view(getFlags)]
+    fn get_flags(&self, #[var_args] subjects: VarArgs<Address>) -> MultiResultVec<bool> {
+        subjects
+            .into_vec()
+            .iter()
+            .map(|subject| self.raised_flags().contains(subject))
+            .collect()
+    }
+
+    #[endpoint(raiseFlag)]
+    fn raise_flag(&self, subject: Address) -> SCResult<()> {
+        let caller = self.blockchain().get_caller();
+        require!(
+            caller == self.blockchain().get_owner_address()
+                || self.raising_access().contains(&caller),
+            "not allowed to raise flags"
+        );
+        if self.raised_flags().insert(subject.clone()) {
+            self.flag_raised_event(&subject);
+        }
+        Ok(())
+    }
+
+    #[endpoint(lowerFlags)]
+    fn lower_flags(&self, #[var_args] subjects: VarArgs<Address>) -> SCResult<()> {
+        only_owner!(self, "Only owner may call this function!");
+        for subject in subjects.into_vec().iter() {
+            if self.raised_flags().remove(subject) {
+                self.flag_lowered_event(subject);
+            }
+        }
+        Ok(())
+    }
+
+    #[endpoint(addRaisingAccess)]
+    fn add_raising_access(&self, raiser: Address) -> SCResult<()> {
+        only_owner!(self, "Only owner may call this function!");
+        require!(self.raising_access().insert(raiser), "Already authorized");
+        Ok(())
+    }
+
+    #[endpoint(removeRaisingAccess)]
+    fn remove_raising_access(&self, raiser: Address) -> SCResult<()> {
+        only_owner!(self, "Only owner may call this function!");
+        require!(
+            self.raising_access().remove(&raiser),
+            "Authorization not found"
+        );
+        Ok(())
+    }
+
+    #[storage_mapper("raised_flags")]
+    fn raised_flags(&self) -> SetMapper<Self::Storage, Address>;
+
+    #[view(raisingAccess)]
+    #[storage_mapper("raising_access")]
+    fn raising_access(&self) -> SetMapper<Self::Storage, Address>;
+
+    #[event("flag_raised")]
+    fn flag_raised_event(&self, #[indexed] subject: &Address);
+
+    #[event("flag_lowered")]
+    fn flag_lowered_event(&self, #[indexed] subject: &Address);
+}
@@ -1,6 +1,7 @@
 #![no_std]
 
 extern crate aggregator;
+use aggregator::type_and_version;
 use elrond_wasm::types::MultiResultVec;
 mod oracle_request;
 use oracle_request::{OracleRequest, RequestView};
@@ -49,6 +50,11 @@ pub trait Oracle {
     #[init]
     fn init(&self) {}
 
+    #[view(typeAndVersion)]
+    fn type_and_version(&self) -> BoxedBytes {
+        type_and_version::type_and_version("Oracle", env!("CARGO_PKG_VERSION"))
+    }
+
     /// This is the entry point that will use the escrow transfer_from.
     /// Afterwards, it essentially calls itself (store_request) which stores the request in state.
     #[endpoint(request)]
@@ -2,10 +2,21 @@
 #![feature(destructuring_assignment)]
 
 elrond_wasm::imports!();
+extern crate aggregator;
+use aggregator::aggregator_interface::Round;
+use aggregator::{deviation, scaling, type_and_version};
 pub mod median;
+pub mod report;
+pub mod usd_value;
 
 mod price_aggregator_data;
-use price_aggregator_data::{OracleStatus, PriceFeed, TokenPair};
+use price_aggregator_data::{
+    DeviationLimits, OracleStatus, PairInfo, PriceBounds, PriceFeed, PriceObservation,
+    PriceSnapshot, TokenPair,
+};
+
+const DEFAULT_HISTORY_LENGTH: u32 = 100;
+const MAX_PRUNED_ROUNDS: u32 = 5;
 
 #[elrond_wasm_derive::contract]
 pub trait PriceAggregator {
@@ -34,6 +45,11 @@ pub trait PriceAggregator {
         Ok(())
     }
 
+    #[view(typeAndVersion)]
+    fn type_and_version(&self) -> BoxedBytes {
+        type_and_version::type_and_version("PriceAggregator", env!("CARGO_PKG_VERSION"))
+    }
+
     #[endpoint]
     #[payable("*")]
     fn deposit(
@@ -74,39 +90,267 @@ pub trait PriceAggregator {
         Ok(())
     }
 
+    /// Withdraws the payments of the caller for all the pairs it submits for in a single
+    /// transfer. The rewards are kept apart from the balance deposited to pay for queries,
+    /// which is left untouched. Returns the claimed amount.
+    #[endpoint(claimAllRewards)]
+    fn claim_all_rewards(&self) -> SCResult<Self::BigUint> {
+        let caller = self.blockchain().get_caller();
+        let mut rewards = self.oracle_rewards(&caller);
+        let mut amount = Self::BigUint::zero();
+        for reward in rewards.values() {
+            amount += &reward;
+        }
+        require!(amount > Self::BigUint::zero(), "no rewards to claim");
+        rewards.clear();
+        self.send()
+            .direct(&caller, &self.payment_token().get(), &amount, &[]);
+        Ok(amount)
+    }
+
     fn transfer(&self, from: Address, to: Address, amount: &Self::BigUint) -> SCResult<()> {
         self.subtract_balance(from, amount)?;
         self.add_balance(to, amount);
         Ok(())
     }
 
+    /// Submits the price of `base` in `quote`, as observed at the source at `observed_at`,
+    /// or at the current block if not given.
+    /// Each pair collects its own submissions and completes its own rounds, so a single
+    /// deployment serves any number of pairs.
     #[endpoint]
-    fn submit(&self, from: BoxedBytes, to: BoxedBytes, price: Self::BigUint) -> SCResult<()> {
-        self.require_is_oracle()?;
-        let token_pair = TokenPair { from, to };
+    fn submit(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        price: Self::BigUint,
+        #[var_args] observed_at: OptionalArg<u64>,
+    ) -> SCResult<()> {
+        let caller = self.blockchain().get_caller();
+        let observed_at = observed_at
+            .into_option()
+            .unwrap_or_else(|| self.blockchain().get_block_timestamp());
+        self.submit_internal(&caller, TokenPair { base, quote }, price, observed_at)
+    }
+
+    /// Submits the prices of several pairs as `(base, quote, price)` entries, so an oracle
+    /// covering many pairs pays for a single transaction per reporting cycle. The prices
+    /// are taken as observed at the current block.
+    /// If any of the entries is rejected, none of them is recorded.
+    #[endpoint(submitBatch)]
+    fn submit_batch(
+        &self,
+        #[var_args] entries: VarArgs<MultiArg3<BoxedBytes, BoxedBytes, Self::BigUint>>,
+    ) -> SCResult<()> {
+        let caller = self.blockchain().get_caller();
+        let observed_at = self.blockchain().get_block_timestamp();
+        for entry in entries.into_vec().into_iter() {
+            let (base, quote, price) = entry.into_tuple();
+            self.submit_internal(&caller, TokenPair { base, quote }, price, observed_at)?;
+        }
+        Ok(())
+    }
+
+    /// Same as `submitBatch`, with `(base, quote, price, observed_at)` entries carrying the
+    /// time each price was observed at its source.
+    #[endpoint(submitBatchObserved)]
+    fn submit_batch_observed(
+        &self,
+        #[var_args] entries: VarArgs<MultiArg4<BoxedBytes, BoxedBytes, Self::BigUint, u64>>,
+    ) -> SCResult<()> {
+        let caller = self.blockchain().get_caller();
+        for entry in entries.into_vec().into_iter() {
+            let (base, quote, price, observed_at) = entry.into_tuple();
+            self.submit_internal(&caller, TokenPair { base, quote }, price, observed_at)?;
+        }
+        Ok(())
+    }
+
+    /// Submits the observations of several oracles for several pairs in a single
+    /// transaction. Every observing oracle signs the whole report (see
+    /// `report::report_message`) with the key of its address, and the report must complete
+    /// the round of every pair it covers. Each accepted report increments the report nonce,
+    /// which is part of the signed message, so that a report cannot be replayed.
+    #[endpoint(submitReport)]
+    fn submit_report(
+        &self,
+        observations: Vec<PriceObservation<Self::BigUint>>,
+        #[var_args] signatures: VarArgs<MultiArg2<Address, BoxedBytes>>,
+    ) -> SCResult<()> {
+        require!(!observations.is_empty(), "empty report");
+        let report_nonce = self.report_nonce().get();
+        let message = report::report_message(
+            &self.blockchain().get_sc_address(),
+            report_nonce,
+            &observations,
+        );
+        let mut signers = Vec::new();
+        for signature in signatures.into_vec().into_iter() {
+            let (oracle, signature) = signature.into_tuple();
+            require!(
+                self.crypto()
+                    .verify_ed25519(oracle.as_bytes(), &message, signature.as_slice()),
+                "invalid report signature"
+            );
+            signers.push(oracle);
+        }
+        let mut token_pairs: Vec<TokenPair> = Vec::new();
+        for observation in observations.into_iter() {
+            require!(
+                signers.contains(&observation.oracle),
+                "observation not signed by its oracle"
+            );
+            let token_pair = TokenPair {
+                base: observation.base,
+                quote: observation.quote,
+            };
+            if !token_pairs.contains(&token_pair) {
+                token_pairs.push(token_pair.clone());
+            }
+            self.submit_internal(
+                &observation.oracle,
+                token_pair,
+                observation.price,
+                observation.observed_at,
+            )?;
+        }
+        for token_pair in token_pairs.iter() {
+            require!(
+                self.submissions()
+                    .get(token_pair)
+                    .map_or(true, |submissions| submissions.is_empty()),
+                "report did not complete the round"
+            );
+        }
+        self.report_nonce().set(&(report_nonce + 1));
+        Ok(())
+    }
+
+    fn submit_internal(
+        &self,
+        oracle: &Address,
+        token_pair: TokenPair,
+        price: Self::BigUint,
+        observed_at: u64,
+    ) -> SCResult<()> {
+        self.require_is_oracle(oracle, &token_pair)?;
+        self.require_fresh_observation(observed_at)?;
+        require!(self.pairs().contains(&token_pair), "pair not listed");
+        require!(
+            !self.pair_replacements().contains_key(&token_pair),
+            "pair deprecated"
+        );
+        require!(
+            self.pair_source(&token_pair).is_empty(),
+            "pair sourced from an aggregator"
+        );
+        require!(!self.paused_pairs().contains(&token_pair), "pair paused");
+        if !self.pair_bounds(&token_pair).is_empty() {
+            require!(
+                self.pair_bounds(&token_pair).get().contains(&price),
+                "price outside of the pair's bounds"
+            );
+        }
         let mut submissions = self
             .submissions()
             .entry(token_pair.clone())
             .or_default()
             .get();
-        let accepted = submissions
-            .insert(self.blockchain().get_caller(), price)
-            .is_none();
+        let accepted = submissions.insert(oracle.clone(), price).is_none();
+        self.submission_observed_at(&token_pair)
+            .insert(oracle.clone(), observed_at);
+        if accepted {
+            self.pay_oracle(oracle, &token_pair);
+        }
         self.oracle_status()
-            .entry(self.blockchain().get_caller())
+            .entry(oracle.clone())
             .and_modify(|oracle_status| {
                 oracle_status.accepted_submissions += accepted as u64;
                 oracle_status.total_submissions += 1;
             });
-        self.create_new_round(token_pair, submissions)?;
+        self.create_new_round(token_pair, submissions)
+    }
+
+    /// Rejects observations from the future, and observations older than the maximum
+    /// observation age if one is set.
+    fn require_fresh_observation(&self, observed_at: u64) -> SCResult<()> {
+        let now = self.blockchain().get_block_timestamp();
+        require!(observed_at <= now, "observation from the future");
+        let max_age = self.max_observation_age().get();
+        require!(
+            max_age == 0 || now - observed_at <= max_age,
+            "observation too old"
+        );
         Ok(())
     }
 
-    fn require_is_oracle(&self) -> SCResult<()> {
+    /// The maximum number of seconds between the observation of a price at its source and
+    /// its submission; 0 disables the check.
+    #[endpoint(setMaxObservationAge)]
+    fn set_max_observation_age(&self, max_observation_age: u64) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        self.max_observation_age().set(&max_observation_age);
+        Ok(())
+    }
+
+    /// The median of the observation times of the submissions of a round.
+    #[view(getRoundObservedAt)]
+    fn get_round_observed_at(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        round_id: u32,
+    ) -> SCResult<u64> {
+        let token_pair = TokenPair { base, quote };
+        let round_observed_at = self.round_observed_at(&token_pair);
+        require!(
+            round_id >= self.get_oldest_round(&token_pair)
+                && round_id as usize <= round_observed_at.len(),
+            "round not found"
+        );
+        Ok(round_observed_at.get(round_id as usize))
+    }
+
+    /// Pays the oracle for its first submission of the round out of the contract's own
+    /// balance, which collects the query payments and can be topped up with `deposit`.
+    /// The payment is credited to the oracle's rewards for the pair. When the balance cannot
+    /// cover it, the payment is skipped so the submission is still accepted.
+    fn pay_oracle(&self, oracle: &Address, token_pair: &TokenPair) {
+        let payment = self.pair_payment_amount(token_pair).get();
+        if payment == Self::BigUint::zero() {
+            return;
+        }
+        let sc_address = self.blockchain().get_sc_address();
+        let balance = self.get_balance(sc_address.clone());
+        if balance < payment {
+            self.oracle_payment_skipped_event(
+                &token_pair.base,
+                &token_pair.quote,
+                oracle,
+                &payment,
+            );
+            return;
+        }
+        self.balance().insert(sc_address, balance - payment.clone());
+        self.oracle_rewards(oracle)
+            .entry(token_pair.clone())
+            .or_default()
+            .update(|reward| *reward += &payment);
+    }
+
+    /// Pairs with their own oracle set only accept submissions from it, the other pairs
+    /// from the global oracles. Oracles suspended from a pair cannot submit for it.
+    fn require_is_oracle(&self, oracle: &Address, token_pair: &TokenPair) -> SCResult<()> {
+        let pair_oracles = self.pair_oracles(token_pair);
+        let allowed = if pair_oracles.is_empty() {
+            self.oracle_status().contains_key(oracle)
+        } else {
+            pair_oracles.contains(oracle)
+        };
+        require!(allowed, "only oracles allowed");
         require!(
-            self.oracle_status()
-                .contains_key(&self.blockchain().get_caller()),
-            "only oracles allowed"
+            !self.suspended_oracles(token_pair).contains(oracle),
+            "oracle suspended for pair"
         );
         Ok(())
     }
@@ -119,16 +363,340 @@ pub trait PriceAggregator {
         if submissions.len() as u32 >= self.submission_count().get() {
             let price_feed =
                 median::calculate(submissions.values().collect())?.ok_or("no submissions")?;
-            self.rounds()
-                .entry(token_pair)
-                .or_default()
-                .get()
-                .push(&price_feed);
+            if !self.pair_bounds(&token_pair).is_empty()
+                && !self.pair_bounds(&token_pair).get().contains(&price_feed)
+            {
+                submissions.clear();
+                self.submission_observed_at(&token_pair).clear();
+                self.paused_pairs().insert(token_pair.clone());
+                self.pair_paused_event(&token_pair.base, &token_pair.quote, &price_feed);
+                return Ok(());
+            }
+            let mut submission_observed_at = self.submission_observed_at(&token_pair);
+            let timestamps: Vec<u64> = submission_observed_at.values().collect();
+            let now = self.blockchain().get_block_timestamp();
+            let (_, median_age, _) = aggregator::median::observation_ages(&timestamps, now);
+            self.push_round(&token_pair, &price_feed, now, now - median_age);
+            self.record_deviations(&token_pair, &submissions, &price_feed);
             submissions.clear();
+            submission_observed_at.clear();
         }
         Ok(())
     }
 
+    /// Records a new round of the pair, completed at `updated_at`.
+    fn push_round(
+        &self,
+        token_pair: &TokenPair,
+        price: &Self::BigUint,
+        updated_at: u64,
+        observed_at: u64,
+    ) {
+        self.pair_updated_at(token_pair).set(&updated_at);
+        self.reported_stale_pairs().remove(token_pair);
+        let round_id = self
+            .rounds()
+            .entry(token_pair.clone())
+            .or_default()
+            .get()
+            .push(price);
+        self.round_observed_at(token_pair).push(&observed_at);
+        self.prune_history(token_pair, round_id as u32);
+    }
+
+    /// Makes a standalone aggregator the source of the pair: the pair no longer accepts
+    /// submissions, and its rounds cache the answers of the aggregator, pulled with
+    /// `refreshPair`. This keeps the submissions isolated per feed while the price
+    /// aggregator stays the single place to read prices from.
+    #[endpoint(setPairSource)]
+    fn set_pair_source(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        aggregator: Address,
+    ) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        let token_pair = TokenPair { base, quote };
+        require!(self.pairs().contains(&token_pair), "pair not listed");
+        require!(
+            self.blockchain().is_smart_contract(&aggregator),
+            "source must be a smart contract"
+        );
+        if let Some(mut submissions) = self.submissions().get(&token_pair) {
+            submissions.clear();
+        }
+        self.submission_observed_at(&token_pair).clear();
+        self.pair_source(&token_pair).set(&aggregator);
+        self.pair_source_round(&token_pair).clear();
+        Ok(())
+    }
+
+    /// Lets the oracles submit for the pair again; its cached rounds are kept.
+    #[endpoint(removePairSource)]
+    fn remove_pair_source(&self, base: BoxedBytes, quote: BoxedBytes) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        let token_pair = TokenPair { base, quote };
+        require!(!self.pair_source(&token_pair).is_empty(), "pair has no source");
+        self.pair_source(&token_pair).clear();
+        self.pair_source_round(&token_pair).clear();
+        Ok(())
+    }
+
+    #[view(getPairSource)]
+    fn get_pair_source(&self, base: BoxedBytes, quote: BoxedBytes) -> OptionalResult<Address> {
+        let source = self.pair_source(&TokenPair { base, quote });
+        if source.is_empty() {
+            OptionalResult::None
+        } else {
+            OptionalResult::Some(source.get())
+        }
+    }
+
+    /// Pulls the latest round of the aggregator the pair is sourced from, caching it as a
+    /// new round of the pair if it is newer than the cached one. Callable by anyone, e.g.
+    /// by keepers polling `pairsNeedingUpdate`.
+    #[endpoint(refreshPair)]
+    fn refresh_pair(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+    ) -> SCResult<AsyncCall<Self::SendApi>> {
+        let token_pair = TokenPair { base, quote };
+        require!(self.pairs().contains(&token_pair), "pair not listed");
+        require!(!self.pair_source(&token_pair).is_empty(), "pair has no source");
+        Ok(self
+            .aggregator_proxy(self.pair_source(&token_pair).get())
+            .latest_round_data()
+            .async_call()
+            .with_callback(self.callbacks().cache_source_round(token_pair)))
+    }
+
+    /// Caches a round of the source aggregator, rescaled to the decimals of the pair.
+    /// Rounds without a single unsigned value, or not newer than the cached one, are
+    /// ignored, as are the rounds of a source replaced while the call was pending.
+    #[callback]
+    fn cache_source_round(
+        &self,
+        #[call_result] result: AsyncCallResult<OptionalArg<Round<Self::BigUint>>>,
+        token_pair: TokenPair,
+    ) {
+        let round = match result {
+            AsyncCallResult::Ok(OptionalArg::Some(round)) => round,
+            _ => return,
+        };
+        if self.pair_source(&token_pair).is_empty()
+            || round.round_id <= self.pair_source_round(&token_pair).get()
+        {
+            return;
+        }
+        let answer = match round.answer {
+            Some(answer) if answer.values.len() == 1 && !answer.is_negative(0) => answer,
+            _ => return,
+        };
+        let price = scaling::rescale(
+            &answer.values[0],
+            round.decimals,
+            self.pair_info_or_default(&token_pair).decimals,
+        );
+        let observed_at = round.updated_at.saturating_sub(round.median_observation_age);
+        self.pair_source_round(&token_pair).set(&round.round_id);
+        self.push_round(&token_pair, &price, round.updated_at, observed_at);
+        self.pair_refreshed_event(&token_pair.base, &token_pair.quote, round.round_id, &price);
+    }
+
+    /// Counts the consecutive rounds in which each oracle deviated from the answer by more
+    /// than the threshold of the pair, suspending the oracle from the pair once it reaches
+    /// the maximum number of strikes. A submission within the threshold clears the strikes.
+    fn record_deviations(
+        &self,
+        token_pair: &TokenPair,
+        submissions: &MapMapper<Self::Storage, Address, Self::BigUint>,
+        answer: &Self::BigUint,
+    ) {
+        if self.pair_deviation_limits(token_pair).is_empty() {
+            return;
+        }
+        let DeviationLimits {
+            threshold,
+            max_strikes,
+        } = self.pair_deviation_limits(token_pair).get();
+        let mut deviation_strikes = self.deviation_strikes(token_pair);
+        for (oracle, price) in submissions.iter() {
+            if !deviation::exceeds_threshold(answer, &price, threshold) {
+                deviation_strikes.remove(&oracle);
+                continue;
+            }
+            let strikes = deviation_strikes.get(&oracle).unwrap_or_default() + 1;
+            if strikes < max_strikes {
+                deviation_strikes.insert(oracle, strikes);
+                continue;
+            }
+            deviation_strikes.remove(&oracle);
+            self.suspended_oracles(token_pair).insert(oracle.clone());
+            self.oracle_suspended_event(&token_pair.base, &token_pair.quote, &oracle, strikes);
+        }
+    }
+
+    /// Suspends the oracles deviating from the answers of the pair by more than `threshold`
+    /// (1% == 1_000) in `max_strikes` consecutive rounds. A `max_strikes` of 0 disables the
+    /// suspensions, dropping the strikes counted so far.
+    #[endpoint(setPairDeviationLimits)]
+    fn set_pair_deviation_limits(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        threshold: u64,
+        max_strikes: u32,
+    ) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        let token_pair = TokenPair { base, quote };
+        require!(self.pairs().contains(&token_pair), "pair not listed");
+        if max_strikes == 0 {
+            self.pair_deviation_limits(&token_pair).clear();
+            self.deviation_strikes(&token_pair).clear();
+        } else {
+            self.pair_deviation_limits(&token_pair).set(&DeviationLimits {
+                threshold,
+                max_strikes,
+            });
+        }
+        Ok(())
+    }
+
+    #[view(getPairDeviationLimits)]
+    fn get_pair_deviation_limits(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+    ) -> OptionalResult<MultiArg2<u64, u32>> {
+        let limits = self.pair_deviation_limits(&TokenPair { base, quote });
+        if limits.is_empty() {
+            OptionalResult::None
+        } else {
+            let DeviationLimits {
+                threshold,
+                max_strikes,
+            } = limits.get();
+            OptionalResult::Some(MultiArg2::from((threshold, max_strikes)))
+        }
+    }
+
+    /// The oracles which deviated from the latest answers of the pair, with the number of
+    /// consecutive rounds they did.
+    #[view(getDeviationStrikes)]
+    fn get_deviation_strikes(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+    ) -> MultiResultVec<MultiArg2<Address, u32>> {
+        self.deviation_strikes(&TokenPair { base, quote })
+            .iter()
+            .map(|(oracle, strikes)| MultiArg2::from((oracle, strikes)))
+            .collect()
+    }
+
+    /// Lets an oracle suspended for its deviations submit for the pair again.
+    #[endpoint(reinstateOracle)]
+    fn reinstate_oracle(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        oracle: Address,
+    ) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        require!(
+            self.suspended_oracles(&TokenPair { base, quote })
+                .remove(&oracle),
+            "oracle not suspended"
+        );
+        Ok(())
+    }
+
+    #[view(getSuspendedOracles)]
+    fn get_suspended_oracles(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+    ) -> MultiResultVec<Address> {
+        self.suspended_oracles(&TokenPair { base, quote })
+            .iter()
+            .collect()
+    }
+
+    /// Clears the rounds of the pair which fell out of its history, a few at a time so
+    /// that shortening the history does not make a single submission too expensive.
+    fn prune_history(&self, token_pair: &TokenPair, latest_round_id: u32) {
+        let history_length = self.get_history_length();
+        if latest_round_id <= history_length {
+            return;
+        }
+        let first_kept_round = latest_round_id - history_length + 1;
+        let oldest_round = self.get_oldest_round(token_pair);
+        if oldest_round >= first_kept_round {
+            return;
+        }
+        let round_values = match self.rounds().get(token_pair) {
+            Some(round_values) => round_values,
+            None => return,
+        };
+        let round_observed_at = self.round_observed_at(token_pair);
+        let end = core::cmp::min(first_kept_round, oldest_round + MAX_PRUNED_ROUNDS);
+        for round_id in oldest_round..end {
+            round_values.clear_entry(round_id as usize);
+            round_observed_at.clear_entry(round_id as usize);
+        }
+        self.oldest_round(token_pair).set(&end);
+    }
+
+    /// The number of rounds kept for each pair, 100 by default.
+    #[endpoint(setHistoryLength)]
+    fn set_history_length(&self, history_length: u32) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        require!(history_length > 0, "history length must be greater than 0");
+        self.history_length().set(&history_length);
+        Ok(())
+    }
+
+    #[view(getHistoryLength)]
+    fn get_history_length(&self) -> u32 {
+        if self.history_length().is_empty() {
+            DEFAULT_HISTORY_LENGTH
+        } else {
+            self.history_length().get()
+        }
+    }
+
+    fn get_oldest_round(&self, token_pair: &TokenPair) -> u32 {
+        core::cmp::max(self.oldest_round(token_pair).get(), 1)
+    }
+
+    /// The round ids and prices of the latest `count` rounds of the pair which are still
+    /// kept, from the newest. Paid with a query payment.
+    #[view(getPriceHistory)]
+    fn get_price_history(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        count: u32,
+    ) -> SCResult<MultiResultVec<MultiArg2<u32, Self::BigUint>>> {
+        self.subtract_query_payment()?;
+        let token_pair = TokenPair { base, quote };
+        self.require_read_access(&token_pair)?;
+        let round_values = self
+            .rounds()
+            .get(&token_pair)
+            .ok_or("token pair not found")?;
+        let latest_round_id = round_values.len() as u32;
+        let first_round_id = core::cmp::max(
+            self.get_oldest_round(&token_pair),
+            (latest_round_id + 1).saturating_sub(count),
+        );
+        Ok((first_round_id..=latest_round_id)
+            .rev()
+            .map(|round_id| MultiArg2::from((round_id, round_values.get(round_id as usize))))
+            .collect())
+    }
+
     #[view(myBalance)]
     fn my_balance(&self) -> Self::BigUint {
         self.get_balance(self.blockchain().get_caller())
@@ -139,46 +707,792 @@ pub trait PriceAggregator {
         self.balance().get(&address).unwrap_or_default()
     }
 
+    /// The unclaimed payments of an oracle, per pair.
+    #[view(getOracleRewards)]
+    fn get_oracle_rewards(
+        &self,
+        oracle: Address,
+    ) -> MultiResultVec<MultiArg3<BoxedBytes, BoxedBytes, Self::BigUint>> {
+        self.oracle_rewards(&oracle)
+            .iter()
+            .map(|(token_pair, reward)| (token_pair.base, token_pair.quote, reward).into())
+            .collect()
+    }
+
     #[view(latestRoundData)]
     fn latest_round_data(&self) -> SCResult<MultiResultVec<PriceFeed<Self::BigUint>>> {
         self.subtract_query_payment()?;
-        require!(!self.rounds().is_empty(), "no completed rounds");
-        Ok(self
+        let caller = self.blockchain().get_caller();
+        let price_feeds: Vec<PriceFeed<Self::BigUint>> = self
             .rounds()
             .iter()
+            .filter(|(token_pair, _)| {
+                self.pairs().contains(token_pair)
+                    && !self.pair_replacements().contains_key(token_pair)
+                    && self.can_read(token_pair, &caller)
+            })
             .map(|(token_pair, round_values)| self.make_price_feed(token_pair, round_values))
+            .collect();
+        require!(!price_feeds.is_empty(), "no completed rounds");
+        Ok(price_feeds.into())
+    }
+
+    /// The latest round of the listed pairs, in listing order, for a single query payment:
+    /// at most `count` pairs starting with the `offset`-th one, or all of them if `count`
+    /// is 0. Pairs without completed rounds and deprecated pairs are left out.
+    #[endpoint(latestPriceFeeds)]
+    fn latest_price_feeds(
+        &self,
+        offset: usize,
+        count: usize,
+    ) -> SCResult<MultiResultVec<PriceFeed<Self::BigUint>>> {
+        self.subtract_query_payment()?;
+        let caller = self.blockchain().get_caller();
+        let count = if count == 0 { usize::MAX } else { count };
+        Ok(self
+            .pairs()
+            .iter()
+            .skip(offset)
+            .take(count)
+            .filter(|token_pair| {
+                !self.pair_replacements().contains_key(token_pair)
+                    && self.can_read(token_pair, &caller)
+            })
+            .filter_map(|token_pair| {
+                self.rounds()
+                    .get(&token_pair)
+                    .filter(|round_values| !round_values.is_empty())
+                    .map(|round_values| self.make_price_feed(token_pair, round_values))
+            })
+            .collect())
+    }
+
+    /// The latest price of every listed pair with completed rounds, along with the time of
+    /// its latest round, for a single query payment. Pairs are in listing order, so that
+    /// bridges and relays can attest the whole board at once. Deprecated pairs are left out.
+    #[view(getPriceSnapshot)]
+    fn get_price_snapshot(&self) -> SCResult<MultiResultVec<PriceSnapshot<Self::BigUint>>> {
+        self.subtract_query_payment()?;
+        let caller = self.blockchain().get_caller();
+        Ok(self
+            .pairs()
+            .iter()
+            .filter(|token_pair| {
+                !self.pair_replacements().contains_key(token_pair)
+                    && self.can_read(token_pair, &caller)
+            })
+            .filter_map(|token_pair| {
+                self.rounds()
+                    .get(&token_pair)
+                    .filter(|round_values| !round_values.is_empty())
+                    .map(|round_values| {
+                        let updated_at = self.pair_updated_at(&token_pair).get();
+                        let feed = self.make_price_feed(token_pair, round_values);
+                        PriceSnapshot {
+                            base: feed.base,
+                            quote: feed.quote,
+                            price: feed.price,
+                            decimals: feed.decimals,
+                            round_id: feed.round_id,
+                            updated_at,
+                        }
+                    })
+            })
             .collect())
     }
 
+    /// The latest round of a pair; the round of its replacement, flagged as deprecated,
+    /// if the pair was deprecated.
     #[endpoint(latestPriceFeed)]
     fn latest_price_feed(
         &self,
-        from: BoxedBytes,
-        to: BoxedBytes,
-    ) -> SCResult<MultiArg5<u32, BoxedBytes, BoxedBytes, Self::BigUint, u8>> {
+        base: BoxedBytes,
+        quote: BoxedBytes,
+    ) -> SCResult<MultiArg6<u32, BoxedBytes, BoxedBytes, Self::BigUint, u8, bool>> {
         self.subtract_query_payment()?;
-        let token_pair = TokenPair { from, to };
+        let (token_pair, deprecated) = self.resolve_pair(TokenPair { base, quote });
+        require!(self.pairs().contains(&token_pair), "token pair not found");
+        self.require_read_access(&token_pair)?;
         let round_values = self
             .rounds()
             .get(&token_pair)
             .ok_or("token pair not found")?;
         let feed = self.make_price_feed(token_pair, round_values);
-        Ok(MultiArg5::from((
+        Ok(MultiArg6::from((
             feed.round_id,
-            feed.from,
-            feed.to,
+            feed.base,
+            feed.quote,
             feed.price,
             feed.decimals,
+            deprecated,
         )))
     }
 
+    /// Like `latestPriceFeed`, provided the pair (or its replacement, if it was deprecated)
+    /// was updated within the last `max_age` seconds.
+    #[view(getPriceNotOlderThan)]
+    fn get_price_not_older_than(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        max_age: u64,
+    ) -> SCResult<MultiArg6<u32, BoxedBytes, BoxedBytes, Self::BigUint, u8, bool>> {
+        let (token_pair, _) = self.resolve_pair(TokenPair {
+            base: base.clone(),
+            quote: quote.clone(),
+        });
+        require!(self.pairs().contains(&token_pair), "token pair not found");
+        self.require_read_access(&token_pair)?;
+        let age = self
+            .blockchain()
+            .get_block_timestamp()
+            .saturating_sub(self.pair_updated_at(&token_pair).get());
+        require!(age <= max_age, "price too old");
+        self.latest_price_feed(base, quote)
+    }
+
+    /// Like `latestPriceFeed`, for a pair given by the tokens whose symbols were registered
+    /// with `setTokenSymbol`.
+    #[endpoint(latestPriceFeedByTokens)]
+    fn latest_price_feed_by_tokens(
+        &self,
+        base: TokenIdentifier,
+        quote: TokenIdentifier,
+    ) -> SCResult<MultiArg6<u32, BoxedBytes, BoxedBytes, Self::BigUint, u8, bool>> {
+        let base = self.symbol_of(&base)?;
+        let quote = self.symbol_of(&quote)?;
+        self.latest_price_feed(base, quote)
+    }
+
+    /// Registers the symbol under which the prices of a token are listed, e.g. `EGLD` for
+    /// the EGLD token. Pairs are keyed by symbols, so assets which are not tokens (fiat
+    /// currencies, indexes) can be listed as well.
+    #[endpoint(setTokenSymbol)]
+    fn set_token_symbol(&self, token: TokenIdentifier, symbol: BoxedBytes) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        require!(!symbol.is_empty(), "invalid symbol");
+        self.token_symbols().insert(token, symbol);
+        Ok(())
+    }
+
+    #[endpoint(removeTokenSymbol)]
+    fn remove_token_symbol(&self, token: TokenIdentifier) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        require!(
+            self.token_symbols().remove(&token).is_some(),
+            "no symbol for token"
+        );
+        Ok(())
+    }
+
+    #[view(getTokenSymbols)]
+    fn get_token_symbols(&self) -> MultiResultVec<MultiArg2<TokenIdentifier, BoxedBytes>> {
+        self.token_symbols()
+            .iter()
+            .map(|(token, symbol)| MultiArg2::from((token, symbol)))
+            .collect()
+    }
+
+    /// The number of decimals of the amounts of a token, needed to value them in USD.
+    #[endpoint(setTokenDecimals)]
+    fn set_token_decimals(&self, token: TokenIdentifier, decimals: u8) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        self.token_decimals().insert(token, decimals);
+        Ok(())
+    }
+
+    #[view(getTokenDecimals)]
+    fn get_token_decimals(&self, token: TokenIdentifier) -> OptionalResult<u8> {
+        self.token_decimals().get(&token).into()
+    }
+
+    /// The USD value of `amount` of the token, from the latest price of its pair with USD,
+    /// with the decimals of that pair. The token needs a registered symbol and decimals,
+    /// and the pair must be within its heartbeat. Paid with a query payment.
+    #[view(getUsdValue)]
+    fn get_usd_value(
+        &self,
+        token: TokenIdentifier,
+        amount: Self::BigUint,
+    ) -> SCResult<Self::BigUint> {
+        self.subtract_query_payment()?;
+        let symbol = self.symbol_of(&token)?;
+        let amount_decimals = self
+            .token_decimals()
+            .get(&token)
+            .ok_or("no decimals for token")?;
+        let feed = self.latest_fresh_price(TokenPair {
+            base: symbol,
+            quote: BoxedBytes::from(usd_value::USD),
+        })?;
+        Ok(usd_value::value_of(&amount, amount_decimals, &feed.price))
+    }
+
+    fn symbol_of(&self, token: &TokenIdentifier) -> SCResult<BoxedBytes> {
+        Ok(self.token_symbols().get(token).ok_or("no symbol for token")?)
+    }
+
+    /// Derives the price of `base` in `quote` from the latest prices of both in `via`,
+    /// e.g. EGLD/BTC from EGLD/USD and BTC/USD, with `decimals` decimals. Both legs must
+    /// have completed rounds and be within their heartbeat. Paid with a query payment.
+    #[view(getDerivedPrice)]
+    fn get_derived_price(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        via: BoxedBytes,
+        decimals: u8,
+    ) -> SCResult<Self::BigUint> {
+        self.subtract_query_payment()?;
+        let base_leg = self.latest_fresh_price(TokenPair {
+            base,
+            quote: via.clone(),
+        })?;
+        let quote_leg = self.latest_fresh_price(TokenPair {
+            base: quote,
+            quote: via,
+        })?;
+        let price = scaling::cross_price(
+            &base_leg.price,
+            base_leg.decimals,
+            &quote_leg.price,
+            quote_leg.decimals,
+            decimals,
+        )
+        .ok_or("zero quote price")?;
+        Ok(price)
+    }
+
+    /// The price of `quote` in `base`, inverted from the latest price of the listed pair
+    /// `base`/`quote`, with the decimals of that pair and rounded to the nearest value.
+    /// The pair must be within its heartbeat. Paid with a query payment.
+    #[view(getInversePrice)]
+    fn get_inverse_price(&self, base: BoxedBytes, quote: BoxedBytes) -> SCResult<Self::BigUint> {
+        self.subtract_query_payment()?;
+        let feed = self.latest_fresh_price(TokenPair { base, quote })?;
+        Ok(scaling::inverse_price(&feed.price, feed.decimals).ok_or("zero price")?)
+    }
+
+    /// The latest price of a listed pair rescaled to `target_decimals` decimals, the
+    /// precision used by the consumer; lowering the precision rounds down. The pair must be
+    /// within its heartbeat. Paid with a query payment.
+    #[view(getPriceScaled)]
+    fn get_price_scaled(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        target_decimals: u8,
+    ) -> SCResult<Self::BigUint> {
+        self.subtract_query_payment()?;
+        let feed = self.latest_fresh_price(TokenPair { base, quote })?;
+        Ok(scaling::rescale(&feed.price, feed.decimals, target_decimals))
+    }
+
+    fn latest_fresh_price(&self, token_pair: TokenPair) -> SCResult<PriceFeed<Self::BigUint>> {
+        let (token_pair, deprecated) = self.resolve_pair(token_pair);
+        require!(self.pairs().contains(&token_pair), "token pair not found");
+        self.require_read_access(&token_pair)?;
+        require!(!self.is_stale(&token_pair), "stale token pair");
+        let round_values = self
+            .rounds()
+            .get(&token_pair)
+            .filter(|round_values| !round_values.is_empty())
+            .ok_or("no completed rounds")?;
+        let mut feed = self.make_price_feed(token_pair, round_values);
+        feed.deprecated = deprecated;
+        Ok(feed)
+    }
+
+    /// Deprecates a pair in favor of another listed pair: the pair stops accepting
+    /// submissions, and reads of its price return the price of the replacement, flagged
+    /// as deprecated, giving the consumers time to migrate.
+    #[endpoint(deprecatePair)]
+    fn deprecate_pair(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        replacement_base: BoxedBytes,
+        replacement_quote: BoxedBytes,
+    ) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        let token_pair = TokenPair { base, quote };
+        let replacement = TokenPair {
+            base: replacement_base,
+            quote: replacement_quote,
+        };
+        require!(self.pairs().contains(&token_pair), "pair not listed");
+        require!(self.pairs().contains(&replacement), "replacement not listed");
+        require!(
+            token_pair.base != replacement.base || token_pair.quote != replacement.quote,
+            "pair cannot replace itself"
+        );
+        require!(
+            !self.pair_replacements().contains_key(&replacement),
+            "replacement is deprecated"
+        );
+        self.pair_deprecated_event(
+            &token_pair.base,
+            &token_pair.quote,
+            &replacement.base,
+            &replacement.quote,
+        );
+        self.pair_replacements().insert(token_pair, replacement);
+        Ok(())
+    }
+
+    #[endpoint(undeprecatePair)]
+    fn undeprecate_pair(&self, base: BoxedBytes, quote: BoxedBytes) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        require!(
+            self.pair_replacements()
+                .remove(&TokenPair { base, quote })
+                .is_some(),
+            "pair not deprecated"
+        );
+        Ok(())
+    }
+
+    #[view(getPairReplacement)]
+    fn get_pair_replacement(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+    ) -> OptionalResult<MultiArg2<BoxedBytes, BoxedBytes>> {
+        self.pair_replacements()
+            .get(&TokenPair { base, quote })
+            .map(|replacement| MultiArg2::from((replacement.base, replacement.quote)))
+            .into()
+    }
+
+    /// Restricts the reads of the price of a pair, e.g. a premium pair, to its readers.
+    #[endpoint(restrictPair)]
+    fn restrict_pair(&self, base: BoxedBytes, quote: BoxedBytes) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        let token_pair = TokenPair { base, quote };
+        require!(self.pairs().contains(&token_pair), "pair not listed");
+        require!(
+            self.restricted_pairs().insert(token_pair),
+            "pair already restricted"
+        );
+        Ok(())
+    }
+
+    /// Lets anyone read the price of a pair again. Its readers are kept, in case the pair
+    /// is restricted again.
+    #[endpoint(unrestrictPair)]
+    fn unrestrict_pair(&self, base: BoxedBytes, quote: BoxedBytes) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        require!(
+            self.restricted_pairs().remove(&TokenPair { base, quote }),
+            "pair not restricted"
+        );
+        Ok(())
+    }
+
+    #[endpoint(addPairReader)]
+    fn add_pair_reader(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        reader: Address,
+    ) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        require!(
+            self.pair_readers(&TokenPair { base, quote }).insert(reader),
+            "already a reader"
+        );
+        Ok(())
+    }
+
+    #[endpoint(removePairReader)]
+    fn remove_pair_reader(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        reader: Address,
+    ) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        require!(
+            self.pair_readers(&TokenPair { base, quote })
+                .remove(&reader),
+            "not a reader"
+        );
+        Ok(())
+    }
+
+    #[view(isPairRestricted)]
+    fn is_pair_restricted(&self, base: BoxedBytes, quote: BoxedBytes) -> bool {
+        self.restricted_pairs().contains(&TokenPair { base, quote })
+    }
+
+    #[view(getPairReaders)]
+    fn get_pair_readers(&self, base: BoxedBytes, quote: BoxedBytes) -> MultiResultVec<Address> {
+        self.pair_readers(&TokenPair { base, quote }).iter().collect()
+    }
+
+    #[view(hasReadAccess)]
+    fn has_read_access(&self, base: BoxedBytes, quote: BoxedBytes, reader: Address) -> bool {
+        self.can_read(&TokenPair { base, quote }, &reader)
+    }
+
+    /// The access control hook shared by every read of a price: restricted pairs can only
+    /// be read by their readers, the other pairs by anyone.
+    fn can_read(&self, token_pair: &TokenPair, reader: &Address) -> bool {
+        !self.restricted_pairs().contains(token_pair)
+            || self.pair_readers(token_pair).contains(reader)
+    }
+
+    fn require_read_access(&self, token_pair: &TokenPair) -> SCResult<()> {
+        require!(
+            self.can_read(token_pair, &self.blockchain().get_caller()),
+            "no read access to pair"
+        );
+        Ok(())
+    }
+
+    /// Follows the replacements of a deprecated pair, returning the pair in use and
+    /// whether the given pair was deprecated. A pair is only ever replaced by a pair which
+    /// is not deprecated itself, so the replacements never form a cycle.
+    fn resolve_pair(&self, token_pair: TokenPair) -> (TokenPair, bool) {
+        let mut resolved = token_pair;
+        let mut deprecated = false;
+        while let Some(replacement) = self.pair_replacements().get(&resolved) {
+            resolved = replacement;
+            deprecated = true;
+        }
+        (resolved, deprecated)
+    }
+
     #[view(latestPriceFeedOptional)]
     fn latest_price_feed_optional(
         &self,
-        from: BoxedBytes,
-        to: BoxedBytes,
-    ) -> OptionalResult<MultiArg5<u32, BoxedBytes, BoxedBytes, Self::BigUint, u8>> {
-        self.latest_price_feed(from, to).ok().into()
+        base: BoxedBytes,
+        quote: BoxedBytes,
+    ) -> OptionalResult<MultiArg6<u32, BoxedBytes, BoxedBytes, Self::BigUint, u8, bool>> {
+        self.latest_price_feed(base, quote).ok().into()
+    }
+
+    /// The number of rounds completed for the pair, which is the id of its latest round.
+    #[view(latestRoundId)]
+    fn latest_round_id(&self, base: BoxedBytes, quote: BoxedBytes) -> u32 {
+        self.rounds()
+            .get(&TokenPair { base, quote })
+            .map_or_else(|| 0, |round_values| round_values.len() as u32)
+    }
+
+    /// The oracles which submitted a price for the round the pair is collecting.
+    #[view(getPendingSubmissions)]
+    fn get_pending_submissions(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+    ) -> MultiResultVec<MultiArg2<Address, Self::BigUint>> {
+        match self.submissions().get(&TokenPair { base, quote }) {
+            Some(submissions) => submissions
+                .iter()
+                .map(|(oracle, price)| MultiArg2::from((oracle, price)))
+                .collect(),
+            None => MultiResultVec::new(),
+        }
+    }
+
+    /// Lists a pair, allowing the oracles to submit its price. A pair listed again after
+    /// being delisted continues from its latest round.
+    #[endpoint(addPair)]
+    fn add_pair(&self, base: BoxedBytes, quote: BoxedBytes) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        require!(!base.is_empty() && !quote.is_empty(), "invalid pair");
+        let token_pair = TokenPair { base, quote };
+        if self.pair_updated_at(&token_pair).get() == 0 {
+            self.pair_updated_at(&token_pair)
+                .set(&self.blockchain().get_block_timestamp());
+        }
+        require!(self.pairs().insert(token_pair), "pair already listed");
+        Ok(())
+    }
+
+    /// Delists a pair: its price is no longer served and the submissions of the round it
+    /// was collecting are dropped. Its completed rounds are kept.
+    #[endpoint(removePair)]
+    fn remove_pair(&self, base: BoxedBytes, quote: BoxedBytes) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        let token_pair = TokenPair { base, quote };
+        require!(self.pairs().remove(&token_pair), "pair not listed");
+        if let Some(mut submissions) = self.submissions().get(&token_pair) {
+            submissions.clear();
+        }
+        self.submission_observed_at(&token_pair).clear();
+        Ok(())
+    }
+
+    #[view(getPairs)]
+    fn get_pairs(&self) -> MultiResultVec<MultiArg2<BoxedBytes, BoxedBytes>> {
+        self.pairs()
+            .iter()
+            .map(|token_pair| MultiArg2::from((token_pair.base, token_pair.quote)))
+            .collect()
+    }
+
+    /// Sets the decimals and description of a listed pair, e.g. 8 decimals for EGLD/USD
+    /// and 18 for BTC/ETH.
+    #[endpoint(setPairInfo)]
+    fn set_pair_info(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        decimals: u8,
+        description: BoxedBytes,
+    ) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        let token_pair = TokenPair { base, quote };
+        require!(self.pairs().contains(&token_pair), "pair not listed");
+        self.pair_info().insert(
+            token_pair,
+            PairInfo {
+                decimals,
+                description,
+            },
+        );
+        Ok(())
+    }
+
+    /// Pairs without their own metadata use the decimals set at deployment and are
+    /// described as "BASE/QUOTE".
+    #[view(getPairInfo)]
+    fn get_pair_info(&self, base: BoxedBytes, quote: BoxedBytes) -> PairInfo {
+        self.pair_info_or_default(&TokenPair { base, quote })
+    }
+
+    fn pair_info_or_default(&self, token_pair: &TokenPair) -> PairInfo {
+        self.pair_info().get(token_pair).unwrap_or_else(|| PairInfo {
+            decimals: self.decimals().get(),
+            description: BoxedBytes::from_concat(&[
+                token_pair.base.as_slice(),
+                b"/",
+                token_pair.quote.as_slice(),
+            ]),
+        })
+    }
+
+    /// The maximum number of seconds between two rounds of the pair before it is considered
+    /// stale; 0 disables the check.
+    #[endpoint(setPairHeartbeat)]
+    fn set_pair_heartbeat(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        heartbeat: u64,
+    ) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        let token_pair = TokenPair { base, quote };
+        require!(self.pairs().contains(&token_pair), "pair not listed");
+        self.pair_heartbeat(&token_pair).set(&heartbeat);
+        Ok(())
+    }
+
+    #[view(getPairHeartbeat)]
+    fn get_pair_heartbeat(&self, base: BoxedBytes, quote: BoxedBytes) -> u64 {
+        self.pair_heartbeat(&TokenPair { base, quote }).get()
+    }
+
+    /// The time of the latest round of the pair, or of its listing if it has no rounds.
+    #[view(getPairUpdatedAt)]
+    fn get_pair_updated_at(&self, base: BoxedBytes, quote: BoxedBytes) -> u64 {
+        self.pair_updated_at(&TokenPair { base, quote }).get()
+    }
+
+    /// The listed pairs which were not updated within their heartbeat.
+    #[view(getStalePairs)]
+    fn get_stale_pairs(&self) -> MultiResultVec<MultiArg2<BoxedBytes, BoxedBytes>> {
+        self.pairs()
+            .iter()
+            .filter(|token_pair| self.is_stale(token_pair))
+            .map(|token_pair| MultiArg2::from((token_pair.base, token_pair.quote)))
+            .collect()
+    }
+
+    /// The stale pairs which accept submissions (neither paused nor deprecated), from the
+    /// most overdue, with the number of seconds since their heartbeat lapsed, so that the
+    /// oracle nodes and keepers polling it can prioritize their work.
+    #[view(pairsNeedingUpdate)]
+    fn pairs_needing_update(&self) -> MultiResultVec<MultiArg3<BoxedBytes, BoxedBytes, u64>> {
+        let now = self.blockchain().get_block_timestamp();
+        let mut overdue_pairs: Vec<(TokenPair, u64)> = self
+            .pairs()
+            .iter()
+            .filter(|token_pair| {
+                self.is_stale(token_pair)
+                    && !self.paused_pairs().contains(token_pair)
+                    && !self.pair_replacements().contains_key(token_pair)
+            })
+            .map(|token_pair| {
+                let lapsed_at = self.pair_updated_at(&token_pair).get()
+                    + self.pair_heartbeat(&token_pair).get();
+                (token_pair, now - lapsed_at)
+            })
+            .collect();
+        overdue_pairs.sort_by(|(_, overdue), (_, other_overdue)| other_overdue.cmp(overdue));
+        overdue_pairs
+            .into_iter()
+            .map(|(token_pair, overdue)| {
+                MultiArg3::from((token_pair.base, token_pair.quote, overdue))
+            })
+            .collect()
+    }
+
+    /// Lets keepers report the stale pairs: emits a `pair_stale` event for each pair which
+    /// became stale since it was last updated. Returns the number of reported pairs.
+    #[endpoint(reportStalePairs)]
+    fn report_stale_pairs(&self) -> u32 {
+        let mut reported = 0u32;
+        for token_pair in self.pairs().iter() {
+            if self.is_stale(&token_pair)
+                && self.reported_stale_pairs().insert(token_pair.clone())
+            {
+                let updated_at = self.pair_updated_at(&token_pair).get();
+                self.pair_stale_event(&token_pair.base, &token_pair.quote, updated_at);
+                reported += 1;
+            }
+        }
+        reported
+    }
+
+    fn is_stale(&self, token_pair: &TokenPair) -> bool {
+        let heartbeat = self.pair_heartbeat(token_pair).get();
+        heartbeat > 0
+            && self.pair_updated_at(token_pair).get() + heartbeat
+                < self.blockchain().get_block_timestamp()
+    }
+
+    /// Sets the band of sane prices of a pair: submissions outside of it are rejected, and
+    /// the pair is paused if the price of a round would fall outside of it.
+    #[endpoint(setPairBounds)]
+    fn set_pair_bounds(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        min: Self::BigUint,
+        max: Self::BigUint,
+    ) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        let token_pair = TokenPair { base, quote };
+        require!(self.pairs().contains(&token_pair), "pair not listed");
+        require!(min <= max, "min price above max price");
+        self.pair_bounds(&token_pair).set(&PriceBounds { min, max });
+        Ok(())
+    }
+
+    #[endpoint(removePairBounds)]
+    fn remove_pair_bounds(&self, base: BoxedBytes, quote: BoxedBytes) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        self.pair_bounds(&TokenPair { base, quote }).clear();
+        Ok(())
+    }
+
+    #[view(getPairBounds)]
+    fn get_pair_bounds(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+    ) -> OptionalResult<MultiArg2<Self::BigUint, Self::BigUint>> {
+        let bounds = self.pair_bounds(&TokenPair { base, quote });
+        if bounds.is_empty() {
+            OptionalResult::None
+        } else {
+            let PriceBounds { min, max } = bounds.get();
+            OptionalResult::Some(MultiArg2::from((min, max)))
+        }
+    }
+
+    /// Lets a pair paused by its bounds accept submissions again.
+    #[endpoint(unpausePair)]
+    fn unpause_pair(&self, base: BoxedBytes, quote: BoxedBytes) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        require!(
+            self.paused_pairs().remove(&TokenPair { base, quote }),
+            "pair not paused"
+        );
+        Ok(())
+    }
+
+    #[view(isPairPaused)]
+    fn is_pair_paused(&self, base: BoxedBytes, quote: BoxedBytes) -> bool {
+        self.paused_pairs().contains(&TokenPair { base, quote })
+    }
+
+    /// The amount paid to the oracles for each submission for the pair, which lets
+    /// high-value pairs pay more than long-tail ones; 0 by default.
+    #[endpoint(setPairPaymentAmount)]
+    fn set_pair_payment_amount(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        payment_amount: Self::BigUint,
+    ) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        let token_pair = TokenPair { base, quote };
+        require!(self.pairs().contains(&token_pair), "pair not listed");
+        self.pair_payment_amount(&token_pair).set(&payment_amount);
+        Ok(())
+    }
+
+    #[view(getPairPaymentAmount)]
+    fn get_pair_payment_amount(&self, base: BoxedBytes, quote: BoxedBytes) -> Self::BigUint {
+        self.pair_payment_amount(&TokenPair { base, quote }).get()
+    }
+
+    /// The funds needed to pay the oracles for a full round of every listed pair.
+    #[view(getRequiredReserve)]
+    fn get_required_reserve(&self) -> Self::BigUint {
+        let submission_count = Self::BigUint::from(self.submission_count().get() as u64);
+        let mut reserve = Self::BigUint::zero();
+        for token_pair in self.pairs().iter() {
+            reserve += &(self.pair_payment_amount(&token_pair).get() * submission_count.clone());
+        }
+        reserve
+    }
+
+    /// The funds available for paying the oracles.
+    #[view(getAvailableFunds)]
+    fn get_available_funds(&self) -> Self::BigUint {
+        self.get_balance(self.blockchain().get_sc_address())
+    }
+
+    /// Restricts the submissions for the pair to the given oracles, e.g. to specialized
+    /// data providers for exotic pairs, who cannot submit for the other pairs unless they
+    /// are global oracles too. An empty set lets the global oracles submit again.
+    #[endpoint(setPairOracles)]
+    fn set_pair_oracles(
+        &self,
+        base: BoxedBytes,
+        quote: BoxedBytes,
+        #[var_args] oracles: VarArgs<Address>,
+    ) -> SCResult<()> {
+        only_owner!(self, "Caller must be owner");
+        let token_pair = TokenPair { base, quote };
+        require!(self.pairs().contains(&token_pair), "pair not listed");
+        let mut pair_oracles = self.pair_oracles(&token_pair);
+        pair_oracles.clear();
+        for oracle in oracles.into_vec().into_iter() {
+            pair_oracles.insert(oracle);
+        }
+        Ok(())
+    }
+
+    /// The oracles allowed to submit for the pair: its own set, or the global oracles.
+    #[view(getPairOracles)]
+    fn get_pair_oracles(&self, base: BoxedBytes, quote: BoxedBytes) -> MultiResultVec<Address> {
+        let pair_oracles = self.pair_oracles(&TokenPair { base, quote });
+        if pair_oracles.is_empty() {
+            self.oracle_status().keys().collect()
+        } else {
+            pair_oracles.iter().collect()
+        }
+    }
+
+    #[view(isPairListed)]
+    fn is_pair_listed(&self, base: BoxedBytes, quote: BoxedBytes) -> bool {
+        self.pairs().contains(&TokenPair { base, quote })
     }
 
     #[endpoint(setSubmissionCount)]
@@ -194,12 +1508,14 @@ pub trait PriceAggregator {
         round_values: VecMapper<Self::Storage, Self::BigUint>,
     ) -> PriceFeed<Self::BigUint> {
         let round_id = round_values.len();
+        let decimals = self.pair_info_or_default(&token_pair).decimals;
         PriceFeed {
             round_id: round_id as u32,
-            from: token_pair.from,
-            to: token_pair.to,
+            base: token_pair.base,
+            quote: token_pair.quote,
             price: round_values.get(round_id),
-            decimals: self.decimals().get(),
+            decimals,
+            deprecated: false,
         }
     }
 
@@ -235,6 +1551,108 @@ pub trait PriceAggregator {
     #[storage_mapper("oracle_status")]
     fn oracle_status(&self) -> MapMapper<Self::Storage, Address, OracleStatus>;
 
+    #[storage_mapper("pairs")]
+    fn pairs(&self) -> SetMapper<Self::Storage, TokenPair>;
+
+    #[storage_mapper("pair_info")]
+    fn pair_info(&self) -> MapMapper<Self::Storage, TokenPair, PairInfo>;
+
+    #[storage_mapper("pair_heartbeat")]
+    fn pair_heartbeat(&self, token_pair: &TokenPair) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[storage_mapper("pair_updated_at")]
+    fn pair_updated_at(&self, token_pair: &TokenPair) -> SingleValueMapper<Self::Storage, u64>;
+
+    /// Stale pairs for which a `pair_stale` event was already emitted
+    #[storage_mapper("reported_stale_pairs")]
+    fn reported_stale_pairs(&self) -> SetMapper<Self::Storage, TokenPair>;
+
+    #[storage_mapper("pair_bounds")]
+    fn pair_bounds(
+        &self,
+        token_pair: &TokenPair,
+    ) -> SingleValueMapper<Self::Storage, PriceBounds<Self::BigUint>>;
+
+    #[storage_mapper("pair_payment_amount")]
+    fn pair_payment_amount(
+        &self,
+        token_pair: &TokenPair,
+    ) -> SingleValueMapper<Self::Storage, Self::BigUint>;
+
+    #[storage_mapper("pair_oracles")]
+    fn pair_oracles(&self, token_pair: &TokenPair) -> SetMapper<Self::Storage, Address>;
+
+    #[storage_mapper("pair_deviation_limits")]
+    fn pair_deviation_limits(
+        &self,
+        token_pair: &TokenPair,
+    ) -> SingleValueMapper<Self::Storage, DeviationLimits>;
+
+    /// The number of consecutive rounds in which each oracle deviated from the answer
+    #[storage_mapper("deviation_strikes")]
+    fn deviation_strikes(&self, token_pair: &TokenPair) -> MapMapper<Self::Storage, Address, u32>;
+
+    #[storage_mapper("suspended_oracles")]
+    fn suspended_oracles(&self, token_pair: &TokenPair) -> SetMapper<Self::Storage, Address>;
+
+    /// The replacement of each deprecated pair
+    #[storage_mapper("pair_replacements")]
+    fn pair_replacements(&self) -> MapMapper<Self::Storage, TokenPair, TokenPair>;
+
+    /// The standalone aggregator each sourced pair caches the answers of
+    #[storage_mapper("pair_source")]
+    fn pair_source(&self, token_pair: &TokenPair) -> SingleValueMapper<Self::Storage, Address>;
+
+    /// The id of the latest round of the source aggregator cached for the pair
+    #[storage_mapper("pair_source_round")]
+    fn pair_source_round(&self, token_pair: &TokenPair) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[proxy]
+    fn aggregator_proxy(&self, to: Address) -> aggregator::Proxy<Self::SendApi>;
+
+    /// Pairs whose price can only be read by their readers
+    #[storage_mapper("restricted_pairs")]
+    fn restricted_pairs(&self) -> SetMapper<Self::Storage, TokenPair>;
+
+    #[storage_mapper("pair_readers")]
+    fn pair_readers(&self, token_pair: &TokenPair) -> SetMapper<Self::Storage, Address>;
+
+    /// Pairs paused because the price of a round fell outside of their bounds
+    #[storage_mapper("paused_pairs")]
+    fn paused_pairs(&self) -> SetMapper<Self::Storage, TokenPair>;
+
+    #[storage_mapper("token_symbols")]
+    fn token_symbols(&self) -> MapMapper<Self::Storage, TokenIdentifier, BoxedBytes>;
+
+    #[storage_mapper("token_decimals")]
+    fn token_decimals(&self) -> MapMapper<Self::Storage, TokenIdentifier, u8>;
+
+    #[view(getMaxObservationAge)]
+    #[storage_mapper("max_observation_age")]
+    fn max_observation_age(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    /// The observation time of the submissions of the round each pair is collecting
+    #[storage_mapper("submission_observed_at")]
+    fn submission_observed_at(
+        &self,
+        token_pair: &TokenPair,
+    ) -> MapMapper<Self::Storage, Address, u64>;
+
+    /// The median observation time of each round of the pair, by round id
+    #[storage_mapper("round_observed_at")]
+    fn round_observed_at(&self, token_pair: &TokenPair) -> VecMapper<Self::Storage, u64>;
+
+    #[view(getReportNonce)]
+    #[storage_mapper("report_nonce")]
+    fn report_nonce(&self) -> SingleValueMapper<Self::Storage, u64>;
+
+    #[storage_mapper("history_length")]
+    fn history_length(&self) -> SingleValueMapper<Self::Storage, u32>;
+
+    /// The oldest round of the pair which was not pruned yet; empty until the first pruning
+    #[storage_mapper("oldest_round")]
+    fn oldest_round(&self, token_pair: &TokenPair) -> SingleValueMapper<Self::Storage, u32>;
+
     #[storage_mapper("rounds")]
     fn rounds(
         &self,
@@ -247,4 +1665,62 @@ pub trait PriceAggregator {
 
     #[storage_mapper("balance")]
     fn balance(&self) -> MapMapper<Self::Storage, Address, Self::BigUint>;
+
+    #[storage_mapper("oracle_rewards")]
+    fn oracle_rewards(
+        &self,
+        oracle: &Address,
+    ) -> MapMapper<Self::Storage, TokenPair, Self::BigUint>;
+
+    #[event("pair_deprecated")]
+    fn pair_deprecated_event(
+        &self,
+        #[indexed] base: &BoxedBytes,
+        #[indexed] quote: &BoxedBytes,
+        #[indexed] replacement_base: &BoxedBytes,
+        replacement_quote: &BoxedBytes,
+    );
+
+    #[event("oracle_suspended")]
+    fn oracle_suspended_event(
+        &self,
+        #[indexed] base: &BoxedBytes,
+        #[indexed] quote: &BoxedBytes,
+        #[indexed] oracle: &Address,
+        strikes: u32,
+    );
+
+    #[event("oracle_payment_skipped")]
+    fn oracle_payment_skipped_event(
+        &self,
+        #[indexed] base: &BoxedBytes,
+        #[indexed] quote: &BoxedBytes,
+        #[indexed] oracle: &Address,
+        payment: &Self::BigUint,
+    );
+
+    #[event("pair_refreshed")]
+    fn pair_refreshed_event(
+        &self,
+        #[indexed] base: &BoxedBytes,
+        #[indexed] quote: &BoxedBytes,
+        #[indexed] source_round_id: u64,
+        price: &Self::BigUint,
+    );
+
+    #[event("pair_paused")]
+    fn pair_paused_event(
+        &self,
+        #[indexed] base: &BoxedBytes,
+        #[indexed] quote: &BoxedBytes,
+        price: &Self::BigUint,
+    );
+
+    #[event("pair_stale")]
+    fn pair_stale_event(
+        &self,
+        #[indexed] base: &BoxedBytes,
+        #[indexed] quote: &BoxedBytes,
+        updated_at: u64,
+    );
 }
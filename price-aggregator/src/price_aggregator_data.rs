@@ -1,19 +1,71 @@
 elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
 
-#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi, Clone)]
+/// A trading pair, priced as the amount of `quote` for one unit of `base`
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi, Clone, PartialEq)]
 pub struct TokenPair {
-    pub from: BoxedBytes,
-    pub to: BoxedBytes,
+    pub base: BoxedBytes,
+    pub quote: BoxedBytes,
+}
+
+/// The observation of an oracle in a report submitted with `submitReport`
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct PriceObservation<BigUint: BigUintApi> {
+    pub oracle: Address,
+    pub base: BoxedBytes,
+    pub quote: BoxedBytes,
+    pub price: BigUint,
+    pub observed_at: u64,
+}
+
+/// The metadata of a pair, as returned by `getPairInfo`
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct PairInfo {
+    pub decimals: u8,
+    pub description: BoxedBytes,
+}
+
+/// The band outside of which the prices of a pair are considered insane
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct PriceBounds<BigUint: BigUintApi> {
+    pub min: BigUint,
+    pub max: BigUint,
+}
+
+impl<BigUint: BigUintApi> PriceBounds<BigUint> {
+    pub fn contains(&self, price: &BigUint) -> bool {
+        price >= &self.min && price <= &self.max
+    }
+}
+
+/// Oracles deviating from the answers of a pair by more than `threshold` (a fraction of
+/// `deviation::THRESHOLD_MULTIPLIER`) in `max_strikes` consecutive rounds are suspended
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct DeviationLimits {
+    pub threshold: u64,
+    pub max_strikes: u32,
 }
 
 #[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
 pub struct PriceFeed<BigUint: BigUintApi> {
     pub round_id: u32,
-    pub from: BoxedBytes,
-    pub to: BoxedBytes,
+    pub base: BoxedBytes,
+    pub quote: BoxedBytes,
     pub price: BigUint,
     pub decimals: u8,
+    /// Set when the requested pair was deprecated, `base` and `quote` being its replacement
+    pub deprecated: bool,
+}
+
+/// An entry of the snapshot of the prices of every pair, as returned by `getPriceSnapshot`
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct PriceSnapshot<BigUint: BigUintApi> {
+    pub base: BoxedBytes,
+    pub quote: BoxedBytes,
+    pub price: BigUint,
+    pub decimals: u8,
+    pub round_id: u32,
+    pub updated_at: u64,
 }
 
 #[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
@@ -0,0 +1,28 @@
+elrond_wasm::imports!();
+use crate::price_aggregator_data::PriceObservation;
+
+/// Builds the message signed by every oracle taking part in a report: the price aggregator
+/// address, the report nonce and then, for each observation, the oracle address followed
+/// by the length-prefixed base, quote and price, and the observation time.
+pub fn report_message<BigUint: BigUintApi>(
+    price_aggregator: &Address,
+    report_nonce: u64,
+    observations: &[PriceObservation<BigUint>],
+) -> Vec<u8> {
+    let mut message = Vec::new();
+    message.extend_from_slice(price_aggregator.as_bytes());
+    message.extend_from_slice(&report_nonce.to_be_bytes());
+    for observation in observations.iter() {
+        message.extend_from_slice(observation.oracle.as_bytes());
+        push_bytes(&mut message, observation.base.as_slice());
+        push_bytes(&mut message, observation.quote.as_slice());
+        push_bytes(&mut message, observation.price.to_bytes_be().as_slice());
+        message.extend_from_slice(&observation.observed_at.to_be_bytes());
+    }
+    message
+}
+
+fn push_bytes(message: &mut Vec<u8>, bytes: &[u8]) {
+    message.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    message.extend_from_slice(bytes);
+}
@@ -0,0 +1,16 @@
+elrond_wasm::imports!();
+
+use aggregator::scaling;
+
+/// The symbol which prices are normalized to
+pub const USD: &[u8] = b"USD";
+
+/// The value of `amount` units of an asset with `amount_decimals` decimals, at `price`
+/// (the price of one unit of the asset), keeping the decimals of the price. Rounded down.
+pub fn value_of<BigUint: BigUintApi>(
+    amount: &BigUint,
+    amount_decimals: u8,
+    price: &BigUint,
+) -> BigUint {
+    amount.clone() * price.clone() / scaling::pow10::<BigUint>(amount_decimals)
+}
@@ -0,0 +1,182 @@
+#![no_std]
+
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+extern crate aggregator;
+use aggregator::median;
+
+/// The attestation of an oracle for the round an asset is collecting
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct Attestation<BigUint: BigUintApi> {
+    pub reserve: BigUint,
+    pub attested_at: u64,
+}
+
+/// The reserve backing a wrapped asset, as returned by `latestReserve`
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct Reserve<BigUint: BigUintApi> {
+    pub round_id: u32,
+    /// In the smallest units of the wrapped asset, so it compares to its supply
+    pub reserve: BigUint,
+    /// The median time at which the custodian balances were attested
+    pub attested_at: u64,
+    /// The time the round completed
+    pub updated_at: u64,
+}
+
+#[elrond_wasm_derive::contract]
+pub trait ReserveFeed {
+    #[init]
+    fn init(&self, submission_count: u32, #[var_args] oracles: VarArgs<Address>) {
+        self.submission_count().set(&submission_count);
+        for oracle in oracles.into_vec().into_iter() {
+            self.oracles().insert(oracle);
+        }
+    }
+
+    /// Submits the reserve backing `asset`, attested at its custodian at `attested_at`.
+    /// Once enough oracles attested the reserve of an asset, the median of their reserves
+    /// completes a new round for it.
+    #[endpoint]
+    fn submit(
+        &self,
+        asset: TokenIdentifier,
+        reserve: Self::BigUint,
+        attested_at: u64,
+    ) -> SCResult<()> {
+        let caller = self.blockchain().get_caller();
+        require!(self.oracles().contains(&caller), "only oracles allowed");
+        require!(
+            attested_at <= self.blockchain().get_block_timestamp(),
+            "attestation from the future"
+        );
+        let mut attestations = self.attestations(&asset);
+        attestations.insert(
+            caller,
+            Attestation {
+                reserve,
+                attested_at,
+            },
+        );
+        if (attestations.len() as u32) < self.submission_count().get() {
+            return Ok(());
+        }
+        let (reserves, timestamps): (Vec<Self::BigUint>, Vec<u64>) = attestations
+            .values()
+            .map(|attestation| (attestation.reserve, attestation.attested_at))
+            .unzip();
+        let reserve = median::calculate(reserves)?.ok_or("no attestations")?;
+        let now = self.blockchain().get_block_timestamp();
+        let (_, median_age, _) = median::observation_ages(&timestamps, now);
+        let attested_at = now - median_age;
+        let round_id = self.latest_round_id(&asset) + 1;
+        self.reserve_updated_event(&asset, round_id, attested_at, &reserve);
+        self.reserves(&asset).set(&Reserve {
+            round_id,
+            reserve,
+            attested_at,
+            updated_at: now,
+        });
+        attestations.clear();
+        Ok(())
+    }
+
+    #[view(latestReserve)]
+    fn latest_reserve(&self, asset: TokenIdentifier) -> SCResult<Reserve<Self::BigUint>> {
+        require!(!self.reserves(&asset).is_empty(), "no reserve reported");
+        Ok(self.reserves(&asset).get())
+    }
+
+    #[view(getReserve)]
+    fn get_reserve(&self, asset: TokenIdentifier) -> SCResult<Self::BigUint> {
+        Ok(self.latest_reserve(asset)?.reserve)
+    }
+
+    #[view(getAttestedAt)]
+    fn get_attested_at(&self, asset: TokenIdentifier) -> SCResult<u64> {
+        Ok(self.latest_reserve(asset)?.attested_at)
+    }
+
+    /// Whether the latest reserve of the asset covers the given supply of the wrapped asset.
+    #[view(isFullyBacked)]
+    fn is_fully_backed(
+        &self,
+        asset: TokenIdentifier,
+        total_supply: Self::BigUint,
+    ) -> SCResult<bool> {
+        Ok(self.latest_reserve(asset)?.reserve >= total_supply)
+    }
+
+    fn latest_round_id(&self, asset: &TokenIdentifier) -> u32 {
+        if self.reserves(asset).is_empty() {
+            0
+        } else {
+            self.reserves(asset).get().round_id
+        }
+    }
+
+    /// The oracles which attested the reserve of the asset for the round it is collecting.
+    #[view(getPendingAttestations)]
+    fn get_pending_attestations(
+        &self,
+        asset: TokenIdentifier,
+    ) -> MultiResultVec<MultiArg3<Address, Self::BigUint, u64>> {
+        self.attestations(&asset)
+            .iter()
+            .map(|(oracle, attestation)| {
+                MultiArg3::from((oracle, attestation.reserve, attestation.attested_at))
+            })
+            .collect()
+    }
+
+    #[endpoint(setSubmissionCount)]
+    fn set_submission_count(&self, submission_count: u32) -> SCResult<()> {
+        only_owner!(self, "Only owner may call this function!");
+        require!(submission_count > 0, "submission count must be greater than 0");
+        self.submission_count().set(&submission_count);
+        Ok(())
+    }
+
+    #[endpoint(addOracle)]
+    fn add_oracle(&self, oracle: Address) -> SCResult<()> {
+        only_owner!(self, "Only owner may call this function!");
+        require!(self.oracles().insert(oracle), "Already authorized");
+        Ok(())
+    }
+
+    #[endpoint(removeOracle)]
+    fn remove_oracle(&self, oracle: Address) -> SCResult<()> {
+        only_owner!(self, "Only owner may call this function!");
+        require!(self.oracles().remove(&oracle), "Authorization not found");
+        Ok(())
+    }
+
+    #[view(getSubmissionCount)]
+    #[storage_mapper("submission_count")]
+    fn submission_count(&self) -> SingleValueMapper<Self::Storage, u32>;
+
+    #[view(getOracles)]
+    #[storage_mapper("oracles")]
+    fn oracles(&self) -> SetMapper<Self::Storage, Address>;
+
+    #[storage_mapper("attestations")]
+    fn attestations(
+        &self,
+        asset: &TokenIdentifier,
+    ) -> MapMapper<Self::Storage, Address, Attestation<Self::BigUint>>;
+
+    #[storage_mapper("reserves")]
+    fn reserves(
+        &self,
+        asset: &TokenIdentifier,
+    ) -> SingleValueMapper<Self::Storage, Reserve<Self::BigUint>>;
+
+    #[event("reserve_updated")]
+    fn reserve_updated_event(
+        &self,
+        #[indexed] asset: &TokenIdentifier,
+        #[indexed] round_id: u32,
+        #[indexed] attested_at: u64,
+        reserve: &Self::BigUint,
+    );
+}
@@ -0,0 +1,79 @@
+use aggregator::deposits;
+use elrond_wasm_debug::api::RustBigUint;
+
+fn check_shares_for_deposit(
+    expected: u64,
+    expected_total: u64,
+    amount: u64,
+    pool: u64,
+    total_shares: u64,
+) {
+    let (actual, actual_total) = deposits::shares_for_deposit::<RustBigUint>(
+        &amount.into(),
+        &pool.into(),
+        &total_shares.into(),
+    );
+    assert_eq!(RustBigUint::from(expected), actual);
+    assert_eq!(RustBigUint::from(expected_total), actual_total);
+}
+
+fn check_claim_for_shares(expected: u64, shares: u64, pool: u64, total_shares: u64) {
+    let actual = deposits::claim_for_shares::<RustBigUint>(
+        &shares.into(),
+        &pool.into(),
+        &total_shares.into(),
+    );
+    assert_eq!(RustBigUint::from(expected), actual);
+}
+
+fn check_shares_for_withdrawal(expected: u64, amount: u64, pool: u64, total_shares: u64) {
+    let actual = deposits::shares_for_withdrawal::<RustBigUint>(
+        &amount.into(),
+        &pool.into(),
+        &total_shares.into(),
+    );
+    assert_eq!(RustBigUint::from(expected), actual);
+}
+
+#[test]
+fn test_shares_for_deposit() {
+    // empty pool
+    check_shares_for_deposit(100, 100, 100, 0, 0);
+
+    // pool worth as much as its shares
+    check_shares_for_deposit(100, 1100, 100, 1000, 1000);
+
+    // pool which already paid some oracles
+    check_shares_for_deposit(200, 1200, 100, 500, 1000);
+
+    // pool which was fully spent, whose shares the contract clears first
+    check_shares_for_deposit(100, 1100, 100, 0, 1000);
+
+    // funds without shares are not claimed by the depositor
+    check_shares_for_deposit(100, 600, 100, 500, 0);
+    check_claim_for_shares(100, 100, 600, 600);
+}
+
+#[test]
+fn test_claim_for_shares() {
+    check_claim_for_shares(0, 100, 1000, 0);
+    check_claim_for_shares(100, 100, 1000, 1000);
+
+    // payments are charged pro rata
+    check_claim_for_shares(150, 300, 500, 1000);
+    check_claim_for_shares(350, 700, 500, 1000);
+
+    // rounded down
+    check_claim_for_shares(33, 1, 100, 3);
+}
+
+#[test]
+fn test_shares_for_withdrawal() {
+    check_shares_for_withdrawal(0, 100, 0, 1000);
+    check_shares_for_withdrawal(100, 100, 1000, 1000);
+    check_shares_for_withdrawal(200, 100, 500, 1000);
+
+    // rounded up
+    check_shares_for_withdrawal(1, 33, 100, 3);
+    check_shares_for_withdrawal(2, 34, 100, 3);
+}
@@ -0,0 +1,67 @@
+use aggregator::aggregator_interface::Submission;
+use aggregator::deviation;
+use elrond_wasm_debug::api::RustBigUint;
+
+fn check_deviation(expected: bool, previous: u64, current: u64, threshold: u64) {
+    let actual = deviation::exceeds_threshold::<RustBigUint>(
+        &previous.into(),
+        &current.into(),
+        threshold,
+    );
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn test_exceeds_threshold() {
+    // 1% threshold
+    check_deviation(false, 1000, 1000, 1_000);
+    check_deviation(false, 1000, 1010, 1_000);
+    check_deviation(true, 1000, 1011, 1_000);
+    check_deviation(false, 1000, 990, 1_000);
+    check_deviation(true, 1000, 989, 1_000);
+
+    // zero threshold flags any change
+    check_deviation(true, 1000, 1001, 0);
+    check_deviation(false, 1000, 1000, 0);
+}
+
+#[test]
+fn test_exceeds_threshold_previous_zero() {
+    check_deviation(false, 0, 1000, 1_000);
+}
+
+#[test]
+fn test_submission_exceeds_threshold() {
+    let previous = Submission::from_values(vec![RustBigUint::from(100u64), RustBigUint::from(200u64)]);
+    let small_change = Submission::from_values(vec![RustBigUint::from(101u64), RustBigUint::from(201u64)]);
+    let large_change = Submission::from_values(vec![RustBigUint::from(101u64), RustBigUint::from(250u64)]);
+    assert!(!deviation::submission_exceeds_threshold(&previous, &small_change, 5_000));
+    assert!(deviation::submission_exceeds_threshold(&previous, &large_change, 5_000));
+}
+
+#[test]
+fn test_submission_sign_change() {
+    let previous = Submission::from_values(vec![RustBigUint::from(100u64)]);
+    let negative = Submission {
+        values: vec![RustBigUint::from(1u64)],
+        negative: vec![true],
+    };
+    assert!(deviation::submission_exceeds_threshold(&previous, &negative, 5_000));
+
+    let zero = Submission::from_values(vec![RustBigUint::from(0u64)]);
+    assert!(!deviation::submission_exceeds_threshold(&zero, &negative, 5_000));
+}
+
+#[test]
+fn test_dispersion_exceeds_band() {
+    let answer = Submission::from_values(vec![RustBigUint::from(1000u64), RustBigUint::from(0u64)]);
+    let narrow = vec![RustBigUint::from(10u64), RustBigUint::from(5u64)];
+    let wide = vec![RustBigUint::from(11u64), RustBigUint::from(5u64)];
+
+    // 1% band
+    assert!(!deviation::dispersion_exceeds_band(&answer, &narrow, 1_000));
+    assert!(deviation::dispersion_exceeds_band(&answer, &wide, 1_000));
+
+    // disabled
+    assert!(!deviation::dispersion_exceeds_band(&answer, &wide, 0));
+}
@@ -37,6 +37,11 @@ fn aggregator() {
     elrond_wasm_debug::mandos_rs("mandos/aggregator.scen.json", &contract_map());
 }
 
+#[test]
+fn aggregator_pause() {
+    elrond_wasm_debug::mandos_rs("mandos/aggregator-pause.scen.json", &contract_map());
+}
+
 #[test]
 fn init_price_aggregator() {
     elrond_wasm_debug::mandos_rs("mandos/init-price-aggregator.scen.json", &contract_map());
@@ -1,5 +1,5 @@
 use aggregator::median;
-use aggregator::aggregator_interface::Submission;
+use aggregator::aggregator_interface::{AggregationStrategy, Submission};
 use elrond_wasm_debug::api::RustBigUint;
 
 fn to_vec_biguint(v: Vec<u32>) -> Vec<RustBigUint> {
@@ -40,23 +40,281 @@ fn test_median_equal() {
     check_median_result(Some(42), vec![42, 42, 42, 42]);
 }
 
+/// Aggregates each of the values of the submissions with `calculate_sorted`, the way the
+/// contract aggregates a round.
+fn calculate_submission_median(
+    submissions: Vec<Submission<RustBigUint>>,
+) -> Option<Submission<RustBigUint>> {
+    let values_count = submissions.first()?.values.len();
+    let values = (0..values_count)
+        .map(|index| {
+            let mut list = Vec::new();
+            for submission in submissions.iter() {
+                median::insert_sorted(&mut list, submission.values[index].clone(), 1);
+            }
+            median::calculate_sorted::<RustBigUint>(&list, AggregationStrategy::Median, 0)
+                .unwrap()
+                .unwrap()
+        })
+        .collect();
+    Some(Submission::from_values(values))
+}
+
 #[test]
 fn test_median_submission_empty() {
-    let actual_result = median::calculate_submission_median::<RustBigUint>(vec![]).unwrap();
-    assert_eq!(actual_result, None);
+    assert_eq!(calculate_submission_median(vec![]), None);
 }
 
 #[test]
 fn test_median_submission() {
-    let submission_a = Submission {
-        values: to_vec_biguint(vec![100, 5000, 6000, 7000, 200, 300, 400])
-    };
-    let submission_b = Submission {
-        values: to_vec_biguint(vec![110, 5010, 6010, 7010, 210, 310, 410])
+    let submission_a =
+        Submission::from_values(to_vec_biguint(vec![100, 5000, 6000, 7000, 200, 300, 400]));
+    let submission_b =
+        Submission::from_values(to_vec_biguint(vec![110, 5010, 6010, 7010, 210, 310, 410]));
+    let expected_submission_result =
+        Submission::from_values(to_vec_biguint(vec![105, 5005, 6005, 7005, 205, 305, 405]));
+    let actual_result = calculate_submission_median(vec![submission_a, submission_b]);
+    assert_eq!(actual_result, Some(expected_submission_result));
+}
+
+#[test]
+fn test_median_submission_odd() {
+    let submissions = vec![
+        Submission::from_values(to_vec_biguint(vec![30, 100])),
+        Submission::from_values(to_vec_biguint(vec![10, 300])),
+        Submission::from_values(to_vec_biguint(vec![20, 200])),
+    ];
+    let expected = Submission::from_values(to_vec_biguint(vec![20, 200]));
+    assert_eq!(calculate_submission_median(submissions), Some(expected));
+}
+
+fn check_strategy_result(
+    expected: Option<u32>,
+    v: Vec<u32>,
+    calculate: fn(Vec<RustBigUint>) -> Result<Option<RustBigUint>, elrond_wasm::types::SCError>,
+) {
+    let expected_biguint: Option<RustBigUint> = expected.map(|value| value.into());
+    assert_eq!(Result::Ok(expected_biguint), calculate(to_vec_biguint(v)));
+}
+
+#[test]
+fn test_mean() {
+    check_strategy_result(None, vec![], median::calculate_mean);
+    check_strategy_result(Some(42), vec![42], median::calculate_mean);
+    check_strategy_result(Some(11), vec![10, 11, 12], median::calculate_mean);
+    check_strategy_result(Some(10), vec![10, 11], median::calculate_mean);
+    check_strategy_result(Some(32), vec![10, 10, 10, 100], median::calculate_mean);
+}
+
+fn check_quantile_result(expected: Option<u32>, v: Vec<u32>, quantile_bp: u32) {
+    let expected_biguint: Option<RustBigUint> = expected.map(|value| value.into());
+    let actual_result = median::calculate_quantile::<RustBigUint>(to_vec_biguint(v), quantile_bp);
+    assert_eq!(Result::Ok(expected_biguint), actual_result);
+}
+
+#[test]
+fn test_quantile() {
+    check_quantile_result(None, vec![], 5_000);
+    check_quantile_result(Some(42), vec![42], 2_500);
+
+    // p50 matches the median
+    check_quantile_result(Some(11), vec![12, 10, 11], 5_000);
+    check_quantile_result(Some(11), vec![10, 11, 12, 13], 5_000);
+
+    check_quantile_result(Some(10), vec![40, 30, 20, 10], 0);
+    check_quantile_result(Some(40), vec![40, 30, 20, 10], 10_000);
+    check_quantile_result(Some(20), vec![10, 20, 30, 40, 50], 2_500);
+    check_quantile_result(Some(40), vec![10, 20, 30, 40, 50], 7_500);
+    check_quantile_result(Some(17), vec![10, 20, 30, 40], 2_500);
+}
+
+#[test]
+fn test_quantile_invalid() {
+    assert!(median::calculate_quantile::<RustBigUint>(to_vec_biguint(vec![1]), 10_001).is_err());
+}
+
+fn to_sorted_list(v: Vec<(u32, u64)>) -> Vec<(RustBigUint, u64)> {
+    let mut list = Vec::new();
+    for (value, weight) in v {
+        median::insert_sorted::<RustBigUint>(&mut list, (value as u64).into(), weight);
+    }
+    list
+}
+
+#[test]
+fn test_insert_sorted() {
+    let list = to_sorted_list(vec![(30, 1), (10, 2), (20, 3), (10, 4)]);
+    let values: Vec<RustBigUint> = list.iter().map(|(value, _)| value.clone()).collect();
+    assert_eq!(values, to_vec_biguint(vec![10, 10, 20, 30]));
+    assert_eq!(list[2].1, 3);
+    assert_eq!(list[3].1, 1);
+}
+
+fn check_sorted_result(
+    expected: Option<u32>,
+    v: Vec<(u32, u64)>,
+    strategy: AggregationStrategy,
+    trim_percent: u8,
+) {
+    let expected_biguint: Option<RustBigUint> = expected.map(|value| value.into());
+    let actual_result =
+        median::calculate_sorted::<RustBigUint>(&to_sorted_list(v), strategy, trim_percent);
+    assert_eq!(Result::Ok(expected_biguint), actual_result);
+}
+
+#[test]
+fn test_calculate_sorted() {
+    check_sorted_result(None, vec![], AggregationStrategy::Median, 0);
+
+    // equal weights pick the middle
+    check_sorted_result(Some(11), vec![(12, 1), (10, 1), (11, 1)], AggregationStrategy::Median, 0);
+    check_sorted_result(Some(11), vec![(13, 2), (10, 2), (12, 2), (11, 2)], AggregationStrategy::Median, 0);
+    check_sorted_result(Some(105), vec![(110, 1), (100, 1)], AggregationStrategy::Median, 0);
+    check_sorted_result(Some(20), vec![(30, 1), (10, 1), (20, 1)], AggregationStrategy::Median, 0);
+
+    // different weights fall back to the weighted median
+    check_sorted_result(Some(12), vec![(12, 3), (10, 1), (11, 1)], AggregationStrategy::Median, 0);
+    check_sorted_result(None, vec![(12, 0), (10, 0)], AggregationStrategy::Median, 0);
+
+    check_sorted_result(Some(20), vec![(40, 1), (10, 1), (10, 1)], AggregationStrategy::Mean, 0);
+    check_sorted_result(Some(12), vec![(12, 1), (10, 1), (12, 1), (11, 1)], AggregationStrategy::Mode, 0);
+    check_sorted_result(
+        Some(11),
+        vec![(1000, 1), (12, 1), (0, 1), (10, 1)],
+        AggregationStrategy::TrimmedMean,
+        25,
+    );
+}
+
+#[test]
+fn test_sorted_weighted_median() {
+    // equal weights behave like the median
+    check_sorted_result(Some(11), vec![(10, 1), (11, 1), (12, 1)], AggregationStrategy::Median, 0);
+    check_sorted_result(Some(42), vec![(42, 1), (43, 1)], AggregationStrategy::Median, 0);
+
+    // a heavier oracle moves the median towards its value
+    check_sorted_result(Some(12), vec![(10, 1), (11, 1), (12, 3)], AggregationStrategy::Median, 0);
+    check_sorted_result(Some(10), vec![(10, 5), (11, 1), (12, 3)], AggregationStrategy::Median, 0);
+    check_sorted_result(Some(11), vec![(10, 2), (12, 2)], AggregationStrategy::Median, 0);
+    check_sorted_result(Some(300), vec![(100, 1), (200, 1), (300, 3)], AggregationStrategy::Median, 0);
+}
+
+#[test]
+fn test_sorted_mean() {
+    check_sorted_result(Some(20), vec![(10, 1), (10, 1), (40, 1)], AggregationStrategy::Mean, 0);
+    check_sorted_result(Some(300), vec![(100, 1), (200, 1), (600, 1)], AggregationStrategy::Mean, 0);
+}
+
+#[test]
+fn test_sorted_mode() {
+    check_sorted_result(None, vec![], AggregationStrategy::Mode, 0);
+    check_sorted_result(Some(42), vec![(42, 1)], AggregationStrategy::Mode, 0);
+    check_sorted_result(Some(11), vec![(10, 1), (11, 1), (12, 1), (11, 1)], AggregationStrategy::Mode, 0);
+    check_sorted_result(
+        Some(12),
+        vec![(12, 1), (10, 1), (12, 1), (11, 1), (12, 1), (10, 1)],
+        AggregationStrategy::Mode,
+        0,
+    );
+    // ties are broken by the smallest value
+    check_sorted_result(Some(10), vec![(12, 1), (10, 1), (12, 1), (10, 1)], AggregationStrategy::Mode, 0);
+}
+
+fn check_sorted_trimmed_mean_result(expected: Option<u32>, v: Vec<u32>, trim_percent: u8) {
+    let list = v.into_iter().map(|value| (value, 1)).collect();
+    check_sorted_result(expected, list, AggregationStrategy::TrimmedMean, trim_percent);
+}
+
+#[test]
+fn test_sorted_trimmed_mean() {
+    check_sorted_trimmed_mean_result(None, vec![], 25);
+    check_sorted_trimmed_mean_result(Some(10), vec![10, 11], 25);
+    check_sorted_trimmed_mean_result(Some(11), vec![10, 11, 1000], 25);
+    check_sorted_trimmed_mean_result(Some(11), vec![0, 10, 12, 1000], 25);
+
+    // no trimming
+    check_sorted_trimmed_mean_result(Some(255), vec![0, 10, 12, 1000], 0);
+
+    // 20% of 10 values discards 2 values at each end
+    check_sorted_trimmed_mean_result(
+        Some(14),
+        vec![1, 2, 12, 13, 14, 14, 15, 16, 900, 1000],
+        20,
+    );
+
+    // a single extreme value is discarded even with a small percentage
+    check_sorted_trimmed_mean_result(Some(25), vec![10, 20, 30, 1_000_000], 1);
+}
+
+#[test]
+fn test_sorted_spread() {
+    let list = to_sorted_list(vec![(110, 1), (100, 1), (105, 1)]);
+    assert_eq!(median::calculate_sorted_spread::<RustBigUint>(&list), RustBigUint::from(10u64));
+    let list = to_sorted_list(vec![(5000, 1), (4000, 1), (4500, 1)]);
+    assert_eq!(median::calculate_sorted_spread::<RustBigUint>(&list), RustBigUint::from(1000u64));
+    assert_eq!(median::calculate_sorted_spread::<RustBigUint>(&[]), RustBigUint::from(0u64));
+}
+
+#[test]
+fn test_shift_signed() {
+    let offset = RustBigUint::from(100u64);
+    let shifted = median::shift_signed::<RustBigUint>(&RustBigUint::from(30u64), true, &offset);
+    assert_eq!(shifted, RustBigUint::from(70u64));
+    assert_eq!(
+        median::unshift_signed::<RustBigUint>(&shifted, &offset),
+        (RustBigUint::from(30u64), true)
+    );
+    assert_eq!(
+        median::unshift_signed::<RustBigUint>(&RustBigUint::from(130u64), &offset),
+        (RustBigUint::from(30u64), false)
+    );
+    assert_eq!(
+        median::unshift_signed::<RustBigUint>(&offset, &offset),
+        (RustBigUint::from(0u64), false)
+    );
+}
+
+#[test]
+fn test_signed_median() {
+    // -20, -5 and 10, shifted by 50
+    let offset = RustBigUint::from(50u64);
+    let mut list = Vec::new();
+    for (magnitude, negative) in [(10u64, false), (20, true), (5, true)] {
+        let shifted = median::shift_signed(&RustBigUint::from(magnitude), negative, &offset);
+        median::insert_sorted::<RustBigUint>(&mut list, shifted, 1);
+    }
+    let median_value =
+        median::calculate_sorted::<RustBigUint>(&list, AggregationStrategy::Median, 0)
+            .unwrap()
+            .unwrap();
+    assert_eq!(
+        median::unshift_signed(&median_value, &offset),
+        (RustBigUint::from(5u64), true)
+    );
+    assert_eq!(median::calculate_sorted_spread(&list), RustBigUint::from(30u64));
+}
+
+#[test]
+fn test_normalize_signs() {
+    let mut submission = Submission {
+        values: to_vec_biguint(vec![0, 10]),
+        negative: vec![true, false],
     };
-    let expected_submission_result = Submission {
-        values: to_vec_biguint(vec![105, 5005, 6005, 7005, 205, 305, 405])
+    submission.normalize_signs();
+    assert!(submission.negative.is_empty());
+
+    let mut submission = Submission {
+        values: to_vec_biguint(vec![0, 10]),
+        negative: vec![true, true],
     };
-    let actual_result = median::calculate_submission_median::<RustBigUint>(vec![submission_a, submission_b]).unwrap();
-    assert_eq!(actual_result, Some(expected_submission_result));
+    submission.normalize_signs();
+    assert_eq!(submission.negative, vec![false, true]);
+    assert!(submission.is_negative(1));
+}
+
+#[test]
+fn test_observation_ages() {
+    assert_eq!(median::observation_ages(&[], 100), (0, 0, 0));
+    assert_eq!(median::observation_ages(&[40, 100, 70], 100), (0, 30, 60));
+    assert_eq!(median::observation_ages(&[90, 60, 100, 75], 100), (0, 17, 40));
 }
@@ -0,0 +1,15 @@
+use aggregator_proxy::{add_phase, parse_round_id};
+
+#[test]
+fn test_add_phase() {
+    assert_eq!(5, add_phase(0, 5));
+    assert_eq!((1u64 << 48) + 5, add_phase(1, 5));
+    assert_eq!((3u64 << 48) + 1234, add_phase(3, 1234));
+}
+
+#[test]
+fn test_parse_round_id() {
+    assert_eq!((0, 5), parse_round_id(5));
+    assert_eq!((1, 5), parse_round_id((1u64 << 48) + 5));
+    assert_eq!((u16::MAX, 7), parse_round_id(add_phase(u16::MAX, 7)));
+}
@@ -0,0 +1,34 @@
+use aggregator::rotation;
+
+#[test]
+fn test_select_committee() {
+    let pool: Vec<u32> = (0..10).collect();
+    let committee = rotation::select_committee(&pool, 4, 12345);
+    assert_eq!(committee.len(), 4);
+    for member in committee.iter() {
+        assert!(pool.contains(member));
+        assert_eq!(committee.iter().filter(|other| *other == member).count(), 1);
+    }
+
+    // deterministic
+    assert_eq!(committee, rotation::select_committee(&pool, 4, 12345));
+    assert_eq!(
+        rotation::select_committee(&pool, 4, 0),
+        rotation::select_committee(&pool, 4, 0)
+    );
+}
+
+#[test]
+fn test_select_committee_small_pool() {
+    let pool: Vec<u32> = vec![1, 2, 3];
+    assert_eq!(rotation::select_committee(&pool, 3, 7), pool);
+    assert_eq!(rotation::select_committee(&pool, 5, 7), pool);
+    assert!(rotation::select_committee(&pool, 0, 7).is_empty());
+}
+
+#[test]
+fn test_seed_from_bytes() {
+    assert_eq!(rotation::seed_from_bytes(&[]), 0);
+    assert_eq!(rotation::seed_from_bytes(&[0, 0, 0, 0, 0, 0, 0, 1]), 1);
+    assert_eq!(rotation::seed_from_bytes(&[0, 0, 0, 0, 0, 0, 0, 1, 1]), 1 ^ (1 << 56));
+}
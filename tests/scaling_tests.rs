@@ -0,0 +1,79 @@
+use aggregator::scaling;
+use elrond_wasm_debug::api::RustBigUint;
+
+fn check_rescale(expected: u64, value: u64, from_decimals: u8, to_decimals: u8) {
+    let actual = scaling::rescale::<RustBigUint>(&value.into(), from_decimals, to_decimals);
+    assert_eq!(RustBigUint::from(expected), actual);
+}
+
+#[test]
+fn test_pow10() {
+    assert_eq!(RustBigUint::from(1u64), scaling::pow10::<RustBigUint>(0));
+    assert_eq!(RustBigUint::from(1_000_000u64), scaling::pow10::<RustBigUint>(6));
+}
+
+#[test]
+fn test_rescale() {
+    // same precision
+    check_rescale(12345, 12345, 2, 2);
+
+    // higher precision
+    check_rescale(123_450_000, 12345, 2, 6);
+    check_rescale(5_000_000_000, 5, 0, 9);
+
+    // lower precision rounds down
+    check_rescale(123, 12345, 4, 2);
+    check_rescale(0, 99, 2, 0);
+}
+
+fn check_cross_price(
+    expected: Option<u64>,
+    base: (u64, u8),
+    quote: (u64, u8),
+    target_decimals: u8,
+) {
+    let actual = scaling::cross_price::<RustBigUint>(
+        &base.0.into(),
+        base.1,
+        &quote.0.into(),
+        quote.1,
+        target_decimals,
+    );
+    assert_eq!(expected.map(RustBigUint::from), actual);
+}
+
+#[test]
+fn test_cross_price() {
+    // EGLD/USD 100.00 and BTC/USD 40000.00 give EGLD/BTC 0.0025
+    check_cross_price(Some(250_000), (10_000, 2), (4_000_000, 2), 8);
+
+    // legs with different decimals
+    check_cross_price(Some(250_000), (10_000, 2), (4_000_000_000_000, 8), 8);
+    check_cross_price(Some(40_000), (40_000_000_000, 6), (10_000, 2), 2);
+
+    // rounded down
+    check_cross_price(Some(33), (1, 0), (3, 0), 2);
+
+    check_cross_price(None, (1, 0), (0, 0), 2);
+}
+
+fn check_inverse_price(expected: Option<u64>, price: u64, decimals: u8) {
+    let actual = scaling::inverse_price::<RustBigUint>(&price.into(), decimals);
+    assert_eq!(expected.map(RustBigUint::from), actual);
+}
+
+#[test]
+fn test_inverse_price() {
+    // EGLD/USD 100.00 gives USD/EGLD 0.01
+    check_inverse_price(Some(1), 10_000, 2);
+    check_inverse_price(Some(1_000_000), 10_000_000_000, 8);
+
+    // rounded to the nearest value
+    check_inverse_price(Some(33), 300, 2);
+    check_inverse_price(Some(67), 150, 2);
+
+    // halves up
+    check_inverse_price(Some(3), 40, 1);
+
+    check_inverse_price(None, 0, 2);
+}
@@ -0,0 +1,36 @@
+use aggregator::twap;
+use elrond_wasm_debug::api::RustBigUint;
+
+fn check_twap(expected: Option<u64>, observations: Vec<(u64, u64)>, window_start: u64, now: u64) {
+    let observations: Vec<(u64, RustBigUint)> = observations
+        .into_iter()
+        .map(|(timestamp, value)| (timestamp, value.into()))
+        .collect();
+    let actual = twap::time_weighted_average::<RustBigUint>(&observations, window_start, now);
+    assert_eq!(expected.map(RustBigUint::from), actual);
+}
+
+#[test]
+fn test_time_weighted_average() {
+    check_twap(None, vec![], 100, 200);
+
+    // a single value in force during the whole window
+    check_twap(Some(42), vec![(50, 42)], 100, 200);
+
+    // 100 during the first 75 seconds, 200 during the last 25
+    check_twap(Some(125), vec![(175, 200), (50, 100)], 100, 200);
+
+    // observations older than the one in force at the window start are ignored
+    check_twap(Some(125), vec![(175, 200), (90, 100), (10, 1_000_000)], 100, 200);
+
+    // the window before the oldest observation is left out
+    check_twap(Some(150), vec![(150, 200), (100, 100)], 0, 200);
+
+    // rounded down
+    check_twap(Some(150), vec![(200, 200), (100, 101)], 0, 300);
+}
+
+#[test]
+fn test_time_weighted_average_empty_window() {
+    check_twap(Some(200), vec![(200, 200), (100, 100)], 200, 200);
+}
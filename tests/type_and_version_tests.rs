@@ -0,0 +1,7 @@
+use aggregator::type_and_version;
+
+#[test]
+fn test_type_and_version() {
+    let value = type_and_version::type_and_version("Aggregator", "1.2.0");
+    assert_eq!(value.as_slice(), b"Aggregator 1.2.0");
+}
@@ -0,0 +1,19 @@
+use elrond_wasm_debug::api::RustBigUint;
+use price_aggregator::usd_value;
+
+fn check_value_of(expected: u64, amount: u64, amount_decimals: u8, price: u64) {
+    let actual = usd_value::value_of::<RustBigUint>(&amount.into(), amount_decimals, &price.into());
+    assert_eq!(RustBigUint::from(expected), actual);
+}
+
+#[test]
+fn test_value_of() {
+    // 2.5 EGLD (18 decimals) at 100.00 USD
+    check_value_of(25_000, 2_500_000_000_000_000_000, 18, 10_000);
+
+    // 3 units of an asset without decimals at 1.50 USD
+    check_value_of(450, 3, 0, 150);
+
+    // rounded down
+    check_value_of(0, 1, 6, 99);
+}
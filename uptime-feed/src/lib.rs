@@ -0,0 +1,191 @@
+#![no_std]
+
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+/// A change of the status of the network, each change starting a new round
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct StatusChange {
+    pub is_up: bool,
+    /// When the network entered this status, as observed by the reporting oracle
+    pub started_at: u64,
+}
+
+/// The current status of the network, as returned by `latestStatus`
+#[derive(NestedEncode, NestedDecode, TopEncode, TopDecode, TypeAbi)]
+pub struct NetworkStatus {
+    pub round_id: u32,
+    pub is_up: bool,
+    pub started_at: u64,
+    /// The latest observation confirming the status
+    pub updated_at: u64,
+}
+
+#[elrond_wasm_derive::contract]
+pub trait UptimeFeed {
+    #[init]
+    fn init(&self, submission_count: u32, #[var_args] oracles: VarArgs<Address>) {
+        self.submission_count().set(&submission_count);
+        for oracle in oracles.into_vec().into_iter() {
+            self.oracles().insert(oracle);
+        }
+    }
+
+    /// Reports whether the network is up, as observed at `timestamp`. Reporting the current
+    /// status only confirms it; once enough oracles reported the same change of status, it
+    /// starts a new round, entered at the median time of their observations.
+    #[endpoint]
+    fn submit(&self, is_up: bool, timestamp: u64) -> SCResult<()> {
+        let caller = self.blockchain().get_caller();
+        require!(self.oracles().contains(&caller), "only oracles allowed");
+        require!(
+            timestamp <= self.blockchain().get_block_timestamp(),
+            "observation from the future"
+        );
+        let mut pending_reports = self.pending_reports();
+        if !self.status().is_empty() {
+            let mut status = self.status().get();
+            require!(timestamp >= status.started_at, "outdated observation");
+            if status.is_up == is_up {
+                pending_reports.remove(&caller);
+                if timestamp > status.updated_at {
+                    status.updated_at = timestamp;
+                    self.status().set(&status);
+                }
+                return Ok(());
+            }
+        }
+        pending_reports.insert(
+            caller,
+            StatusChange {
+                is_up,
+                started_at: timestamp,
+            },
+        );
+        let mut timestamps: Vec<u64> = pending_reports
+            .values()
+            .filter(|report| report.is_up == is_up)
+            .map(|report| report.started_at)
+            .collect();
+        if (timestamps.len() as u32) < self.submission_count().get() {
+            return Ok(());
+        }
+        timestamps.sort_unstable();
+        let timestamp = timestamps[timestamps.len() / 2];
+        pending_reports.clear();
+        let round_id = self.status_changes().push(&StatusChange {
+            is_up,
+            started_at: timestamp,
+        }) as u32;
+        self.status().set(&NetworkStatus {
+            round_id,
+            is_up,
+            started_at: timestamp,
+            updated_at: timestamp,
+        });
+        self.status_changed_event(round_id, is_up, timestamp);
+        Ok(())
+    }
+
+    #[view(isNetworkUp)]
+    fn is_network_up(&self) -> SCResult<bool> {
+        Ok(self.latest_status()?.is_up)
+    }
+
+    #[view(latestStatus)]
+    fn latest_status(&self) -> SCResult<NetworkStatus> {
+        require!(!self.status().is_empty(), "no status reported");
+        Ok(self.status().get())
+    }
+
+    /// The number of seconds the network has been down for; 0 while it is up.
+    #[view(getDowntime)]
+    fn get_downtime(&self) -> SCResult<u64> {
+        let status = self.latest_status()?;
+        if status.is_up {
+            return Ok(0);
+        }
+        Ok(self.blockchain().get_block_timestamp() - status.started_at)
+    }
+
+    /// The number of seconds since the network came back up, which consumers compare to a
+    /// grace period before trusting prices again; 0 while it is down.
+    #[view(getTimeSinceUp)]
+    fn get_time_since_up(&self) -> SCResult<u64> {
+        let status = self.latest_status()?;
+        if !status.is_up {
+            return Ok(0);
+        }
+        Ok(self.blockchain().get_block_timestamp() - status.started_at)
+    }
+
+    /// The status the network entered at the given round, starting from 1.
+    #[view(getStatusChange)]
+    fn get_status_change(&self, round_id: u32) -> SCResult<MultiArg2<bool, u64>> {
+        let status_changes = self.status_changes();
+        require!(
+            round_id > 0 && round_id as usize <= status_changes.len(),
+            "invalid round"
+        );
+        let StatusChange { is_up, started_at } = status_changes.get(round_id as usize);
+        Ok(MultiArg2::from((is_up, started_at)))
+    }
+
+    /// The changes of status reported by oracles which are not confirmed by enough oracles
+    /// yet.
+    #[view(getPendingReports)]
+    fn get_pending_reports(&self) -> MultiResultVec<MultiArg3<Address, bool, u64>> {
+        self.pending_reports()
+            .iter()
+            .map(|(oracle, report)| MultiArg3::from((oracle, report.is_up, report.started_at)))
+            .collect()
+    }
+
+    #[endpoint(setSubmissionCount)]
+    fn set_submission_count(&self, submission_count: u32) -> SCResult<()> {
+        only_owner!(self, "Only owner may call this function!");
+        require!(submission_count > 0, "submission count must be greater than 0");
+        self.submission_count().set(&submission_count);
+        Ok(())
+    }
+
+    #[endpoint(addOracle)]
+    fn add_oracle(&self, oracle: Address) -> SCResult<()> {
+        only_owner!(self, "Only owner may call this function!");
+        require!(self.oracles().insert(oracle), "Already authorized");
+        Ok(())
+    }
+
+    #[endpoint(removeOracle)]
+    fn remove_oracle(&self, oracle: Address) -> SCResult<()> {
+        only_owner!(self, "Only owner may call this function!");
+        require!(self.oracles().remove(&oracle), "Authorization not found");
+        self.pending_reports().remove(&oracle);
+        Ok(())
+    }
+
+    #[view(getSubmissionCount)]
+    #[storage_mapper("submission_count")]
+    fn submission_count(&self) -> SingleValueMapper<Self::Storage, u32>;
+
+    #[view(getOracles)]
+    #[storage_mapper("oracles")]
+    fn oracles(&self) -> SetMapper<Self::Storage, Address>;
+
+    #[storage_mapper("pending_reports")]
+    fn pending_reports(&self) -> MapMapper<Self::Storage, Address, StatusChange>;
+
+    #[storage_mapper("status")]
+    fn status(&self) -> SingleValueMapper<Self::Storage, NetworkStatus>;
+
+    #[storage_mapper("status_changes")]
+    fn status_changes(&self) -> VecMapper<Self::Storage, StatusChange>;
+
+    #[event("status_changed")]
+    fn status_changed_event(
+        &self,
+        #[indexed] round_id: u32,
+        #[indexed] is_up: bool,
+        started_at: u64,
+    );
+}